@@ -0,0 +1,221 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+use alloy_dyn_abi::{DynSolValue, EventExt, FunctionExt, JsonAbiExt};
+use alloy_json_abi::{Event, Function, JsonAbi, Param};
+use alloy_primitives::{hex, Address, Selector, B256};
+use eyre::Context;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::error::{AppError, Result};
+
+/// 解码后的单个参数；value 为 JSON 形式（整数以十进制字符串表示，避免精度丢失）
+#[derive(Debug, Clone, Serialize)]
+pub struct DecodedParam {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: String,
+    pub value: Value,
+}
+
+/// 解码后的函数调用（calldata，及可选的返回数据）
+#[derive(Debug, Clone, Serialize)]
+pub struct DecodedCall {
+    pub function: String,
+    pub signature: String,
+    pub inputs: Vec<DecodedParam>,
+    /// 返回数据（仅追踪输出中可用）
+    pub outputs: Option<Vec<DecodedParam>>,
+}
+
+/// 解码后的事件日志
+#[derive(Debug, Clone, Serialize)]
+pub struct DecodedEvent {
+    pub event: String,
+    pub signature: String,
+    pub params: Vec<DecodedParam>,
+}
+
+fn fmt_params(f: &mut fmt::Formatter<'_>, name: &str, params: &[DecodedParam]) -> fmt::Result {
+    let args: Vec<String> = params.iter().map(|p| format!("{}={}", p.name, p.value)).collect();
+    write!(f, "{name}({})", args.join(", "))
+}
+
+impl fmt::Display for DecodedCall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_params(f, &self.function, &self.inputs)?;
+        if let Some(outputs) = &self.outputs {
+            let ret: Vec<String> = outputs.iter().map(|p| p.value.to_string()).collect();
+            write!(f, " -> ({})", ret.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for DecodedEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_params(f, &self.event, &self.params)
+    }
+}
+
+/// ABI 注册表：目录中的 ABI 文件按文件名作为键。
+///
+/// - `0x<40 位十六进制>.json`：合约地址
+/// - `0x<64 位十六进制>.json`：运行时代码哈希（同一份代码部署多次时使用）
+///
+/// 文件内容可以是 ABI 数组，也可以是带 `abi` 字段的 Hardhat/Foundry 编译产物。
+#[derive(Debug, Clone, Default)]
+pub struct AbiRegistry {
+    by_address: HashMap<Address, JsonAbi>,
+    by_code_hash: HashMap<B256, JsonAbi>,
+}
+
+impl AbiRegistry {
+    /// 加载目录下全部 `.json` 文件；文件名无法识别为地址或代码哈希的文件会被跳过
+    pub fn load_dir(dir: &Path) -> Result<Self> {
+        let mut registry = Self::default();
+        let entries = std::fs::read_dir(dir).with_context(|| format!("read ABI dir {}", dir.display()))?;
+        for entry in entries {
+            let path = entry.context("read ABI dir entry")?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            let Some(key) = path.file_name().and_then(|n| n.to_str()).and_then(|n| n.split('.').next()) else {
+                continue;
+            };
+            let abi = || load_abi_file(&path);
+            match key.len() {
+                42 => match key.parse::<Address>() {
+                    Ok(address) => {
+                        registry.by_address.insert(address, abi()?);
+                    }
+                    Err(_) => tracing::warn!(file = %path.display(), "Skipping ABI file with invalid address name"),
+                },
+                66 => match key.parse::<B256>() {
+                    Ok(hash) => {
+                        registry.by_code_hash.insert(hash, abi()?);
+                    }
+                    Err(_) => tracing::warn!(file = %path.display(), "Skipping ABI file with invalid code hash name"),
+                },
+                _ => tracing::warn!(file = %path.display(), "Skipping ABI file not named by address or code hash"),
+            }
+        }
+        tracing::info!(
+            dir = %dir.display(),
+            addresses = registry.by_address.len(),
+            code_hashes = registry.by_code_hash.len(),
+            "Loaded ABI registry"
+        );
+        Ok(registry)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_address.is_empty() && self.by_code_hash.is_empty()
+    }
+
+    /// 查找合约 ABI：先按地址，再按代码哈希（`code_hash` 仅在需要时才计算）
+    pub fn find(&self, address: Address, code_hash: impl FnOnce() -> Option<B256>) -> Option<&JsonAbi> {
+        if let Some(abi) = self.by_address.get(&address) {
+            return Some(abi);
+        }
+        if self.by_code_hash.is_empty() {
+            return None;
+        }
+        code_hash().and_then(|h| self.by_code_hash.get(&h))
+    }
+
+    /// 解码 calldata（及可选的返回数据）；ABI 中没有对应 selector 或解码失败时返回 None
+    pub fn decode_call(abi: &JsonAbi, input: &[u8], output: Option<&[u8]>) -> Option<DecodedCall> {
+        let selector = Selector::from_slice(input.get(..4)?);
+        let function = abi.functions().find(|f| f.selector() == selector)?;
+        let inputs = function
+            .abi_decode_input(&input[4..])
+            .inspect_err(|e| tracing::debug!(function = %function.name, error = %e, "Calldata decode failed"))
+            .ok()?;
+        let outputs = output.and_then(|data| decode_outputs(function, data));
+        Some(DecodedCall {
+            function: function.name.clone(),
+            signature: function.signature(),
+            inputs: named(&function.inputs, inputs),
+            outputs,
+        })
+    }
+
+    /// 解码事件日志（按 topic0 匹配非匿名事件）
+    pub fn decode_log(abi: &JsonAbi, topics: &[B256], data: &[u8]) -> Option<DecodedEvent> {
+        let topic0 = *topics.first()?;
+        let event = abi.events().find(|e| !e.anonymous && e.selector() == topic0)?;
+        decode_event(event, topics, data)
+    }
+}
+
+fn decode_outputs(function: &Function, data: &[u8]) -> Option<Vec<DecodedParam>> {
+    function
+        .abi_decode_output(data)
+        .inspect_err(|e| tracing::debug!(function = %function.name, error = %e, "Return data decode failed"))
+        .ok()
+        .map(|values| named(&function.outputs, values))
+}
+
+/// 按事件定义解码一条日志，indexed 与非 indexed 参数按声明顺序合并
+pub fn decode_event(event: &Event, topics: &[B256], data: &[u8]) -> Option<DecodedEvent> {
+    let decoded = event
+        .decode_log_parts(topics.iter().copied(), data)
+        .inspect_err(|e| tracing::debug!(event = %event.name, error = %e, "Log decode failed"))
+        .ok()?;
+    let (mut indexed, mut body) = (decoded.indexed.into_iter(), decoded.body.into_iter());
+    let params = event
+        .inputs
+        .iter()
+        .map(|p| {
+            let value = if p.indexed { indexed.next() } else { body.next() };
+            DecodedParam {
+                name: p.name.clone(),
+                ty: p.selector_type().into_owned(),
+                value: value.as_ref().map(sol_to_json).unwrap_or(Value::Null),
+            }
+        })
+        .collect();
+    Some(DecodedEvent { event: event.name.clone(), signature: event.signature(), params })
+}
+
+fn named(params: &[Param], values: Vec<DynSolValue>) -> Vec<DecodedParam> {
+    params
+        .iter()
+        .zip(values)
+        .map(|(p, v)| DecodedParam { name: p.name.clone(), ty: p.selector_type().into_owned(), value: sol_to_json(&v) })
+        .collect()
+}
+
+/// DynSolValue 转 JSON：整数转十进制字符串，字节转 0x 十六进制，数组/元组转 JSON 数组
+pub fn sol_to_json(value: &DynSolValue) -> Value {
+    match value {
+        DynSolValue::Bool(b) => Value::Bool(*b),
+        DynSolValue::Int(i, _) => Value::String(i.to_string()),
+        DynSolValue::Uint(u, _) => Value::String(u.to_string()),
+        DynSolValue::FixedBytes(word, size) => Value::String(hex::encode_prefixed(&word[..*size])),
+        DynSolValue::Address(a) => Value::String(a.to_checksum(None)),
+        DynSolValue::Function(f) => Value::String(hex::encode_prefixed(f.as_slice())),
+        DynSolValue::Bytes(b) => Value::String(hex::encode_prefixed(b)),
+        DynSolValue::String(s) => Value::String(s.clone()),
+        DynSolValue::Array(items) | DynSolValue::FixedArray(items) | DynSolValue::Tuple(items) => {
+            Value::Array(items.iter().map(sol_to_json).collect())
+        }
+        #[allow(unreachable_patterns)]
+        other => Value::String(format!("{other:?}")),
+    }
+}
+
+fn load_abi_file(path: &Path) -> Result<JsonAbi> {
+    let raw = std::fs::read_to_string(path).with_context(|| format!("read ABI file {}", path.display()))?;
+    let value: Value = serde_json::from_str(&raw)
+        .map_err(|e| AppError::InvalidArg(format!("ABI file {}: {e}", path.display())))?;
+    // 编译产物：取 abi 字段
+    let abi = match value {
+        Value::Object(mut obj) if obj.contains_key("abi") => obj.remove("abi").unwrap_or_default(),
+        other => other,
+    };
+    serde_json::from_value(abi).map_err(|e| AppError::InvalidArg(format!("ABI file {}: {e}", path.display())))
+}
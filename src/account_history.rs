@@ -0,0 +1,140 @@
+use std::ops::RangeInclusive;
+
+use alloy_primitives::{Address, B256, KECCAK256_EMPTY, U256};
+use reth_db::{
+    cursor::{DbCursorRO, DbDupCursorRO},
+    tables,
+    transaction::DbTx,
+    Database,
+};
+use reth_db_api::models::ShardedKey;
+use reth_primitives::Account;
+use reth_provider::AccountReader; // basic_account()
+use serde::Serialize;
+
+use crate::databases::BscDatabase;
+use crate::error::{AppError, Result};
+use crate::info::DataKind;
+use crate::output::Record;
+
+/// 账户在某区块的一次变更（before 为区块执行前，after 为区块执行后；账户不存在时为 None）
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountChange {
+    pub block_number: u64,
+    /// 变化的字段：balance|nonce|code
+    pub changed: String,
+    pub balance_before: Option<U256>,
+    pub balance_after: Option<U256>,
+    pub nonce_before: Option<u64>,
+    pub nonce_after: Option<u64>,
+    pub code_hash_before: Option<B256>,
+    pub code_hash_after: Option<B256>,
+}
+
+impl Record for AccountChange {
+    fn text(&self) -> String {
+        let mut parts = vec![format!("block {}:", self.block_number)];
+        if self.balance_before != self.balance_after {
+            parts.push(format!("balance {} -> {}", opt(self.balance_before), opt(self.balance_after)));
+        }
+        if self.nonce_before != self.nonce_after {
+            parts.push(format!("nonce {} -> {}", opt(self.nonce_before), opt(self.nonce_after)));
+        }
+        if self.code_hash_before != self.code_hash_after {
+            let hash = |h: Option<B256>| h.map(|h| format!("{h:#x}")).unwrap_or_else(|| "-".to_string());
+            parts.push(format!("code {} -> {}", hash(self.code_hash_before), hash(self.code_hash_after)));
+        }
+        parts.join(" ")
+    }
+}
+
+fn opt<T: ToString>(v: Option<T>) -> String {
+    v.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string())
+}
+
+/// 账户的可比较状态：(余额, nonce, 代码哈希)；空账户与不存在的账户等价
+fn account_state(account: Option<&Account>) -> (U256, u64, Option<B256>) {
+    match account {
+        Some(a) => (a.balance, a.nonce, a.bytecode_hash.filter(|h| *h != KECCAK256_EMPTY)),
+        None => (U256::ZERO, 0, None),
+    }
+}
+
+/// 从 AccountsHistory 读取账户发生变更的全部区块号（升序）
+pub fn account_change_blocks(db: &BscDatabase, address: Address) -> Result<Vec<u64>> {
+    let tx = db.mdbx().tx()?;
+    let mut cursor = tx.cursor_read::<tables::AccountsHistory>()?;
+    let mut blocks = Vec::new();
+    let mut entry = cursor.seek(ShardedKey::new(address, 0))?;
+    while let Some((key, list)) = entry {
+        if key.key != address {
+            break;
+        }
+        blocks.extend(list.iter());
+        entry = cursor.next()?;
+    }
+    Ok(blocks)
+}
+
+/// 列出 `range` 内账户余额、nonce 或代码发生变化的每个区块及前后值。
+///
+/// before 取自 AccountChangeSets；after 取下一次变更的 before，最后一次变更读取该区块后的历史状态。
+/// 仅被触碰（如只改了存储）的区块会被跳过。
+pub fn account_history(db: &BscDatabase, address: Address, range: RangeInclusive<u64>) -> Result<Vec<AccountChange>> {
+    let history = db.cached_availability()?.range(DataKind::AccountHistory);
+    if let Some(first) = history.and_then(|r| r.first).filter(|first| first > range.start()) {
+        tracing::warn!(first, "Account history below this block is pruned; earlier changes are not listed");
+    }
+
+    let blocks: Vec<u64> =
+        account_change_blocks(db, address)?.into_iter().filter(|b| range.contains(b)).collect();
+    tracing::debug!(%address, blocks = blocks.len(), "Account history blocks");
+
+    let tx = db.mdbx().tx()?;
+    let mut changesets = tx.cursor_dup_read::<tables::AccountChangeSets>()?;
+    let mut befores = Vec::with_capacity(blocks.len());
+    for &block in &blocks {
+        let before = changesets
+            .seek_by_key_subkey(block, address)?
+            .filter(|entry| entry.address == address)
+            .ok_or_else(|| {
+                AppError::Corrupt(format!("account {address:#x} indexed at block {block} but missing from changeset"))
+            })?;
+        befores.push(before.info);
+    }
+
+    let mut changes = Vec::new();
+    for (i, &block) in blocks.iter().enumerate() {
+        let before = befores[i];
+        let after = match befores.get(i + 1) {
+            Some(next) => *next,
+            None => db.state_at_block(block)?.basic_account(&address)?,
+        };
+        let (b0, n0, c0) = account_state(before.as_ref());
+        let (b1, n1, c1) = account_state(after.as_ref());
+        let mut changed = Vec::new();
+        if b0 != b1 {
+            changed.push("balance");
+        }
+        if n0 != n1 {
+            changed.push("nonce");
+        }
+        if c0 != c1 {
+            changed.push("code");
+        }
+        if changed.is_empty() {
+            continue;
+        }
+        changes.push(AccountChange {
+            block_number: block,
+            changed: changed.join("|"),
+            balance_before: before.map(|a| a.balance),
+            balance_after: after.map(|a| a.balance),
+            nonce_before: before.map(|a| a.nonce),
+            nonce_after: after.map(|a| a.nonce),
+            code_hash_before: c0,
+            code_hash_after: c1,
+        });
+    }
+    Ok(changes)
+}
@@ -0,0 +1,534 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use alloy_consensus::transaction::Transaction; // to()/nonce()
+use alloy_primitives::{Address, B256};
+use clap::ValueEnum;
+use eyre::Context;
+use reth_provider::{BlockBodyIndicesProvider, TransactionsProvider};
+use serde::{Deserialize, Serialize};
+
+use crate::al::recover_senders;
+use crate::blocks::BlockIter;
+use crate::databases::{BscDatabase, BscProvider};
+use crate::error::{AppError, Result};
+use crate::output::Record;
+use crate::parallel::{ParallelScan, RangeScanner};
+
+/// 索引格式版本（记录布局变化时递增）
+const INDEX_VERSION: u32 = 1;
+const MANIFEST_FILE: &str = "manifest.json";
+/// 单条记录：address(20) + tx_num(8, 大端) + roles(1)
+const ENTRY_SIZE: usize = 29;
+/// 内存中累积的条目数超过该值时落盘为一个 run
+const FLUSH_ENTRIES: usize = 8_000_000;
+
+/// 地址在交易中的角色
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum AddressRole {
+    /// 交易发送者
+    From,
+    /// 交易接收者
+    To,
+    /// 顶层合约创建交易生成的合约
+    Created,
+    /// 交易回执中的日志发出者
+    Log,
+}
+
+impl AddressRole {
+    pub const ALL: [AddressRole; 4] = [Self::From, Self::To, Self::Created, Self::Log];
+
+    pub fn bit(self) -> u8 {
+        match self {
+            Self::From => 1,
+            Self::To => 1 << 1,
+            Self::Created => 1 << 2,
+            Self::Log => 1 << 3,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::From => "from",
+            Self::To => "to",
+            Self::Created => "created",
+            Self::Log => "log",
+        }
+    }
+
+    /// 角色集合的位掩码（空集合表示全部角色）
+    pub fn mask(roles: &[AddressRole]) -> u8 {
+        if roles.is_empty() {
+            return Self::ALL.iter().fold(0, |m, r| m | r.bit());
+        }
+        roles.iter().fold(0, |m, r| m | r.bit())
+    }
+
+    /// 位掩码转为 `from|log` 形式
+    pub fn describe(mask: u8) -> String {
+        let names: Vec<_> = Self::ALL.iter().filter(|r| mask & r.bit() != 0).map(|r| r.name()).collect();
+        names.join("|")
+    }
+}
+
+/// 索引条目：同一 (address, tx_num) 的多个角色合并为一条
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexEntry {
+    pub address: Address,
+    pub tx_num: u64,
+    pub roles: u8,
+}
+
+impl IndexEntry {
+    fn encode(&self) -> [u8; ENTRY_SIZE] {
+        let mut buf = [0u8; ENTRY_SIZE];
+        buf[..20].copy_from_slice(self.address.as_slice());
+        buf[20..28].copy_from_slice(&self.tx_num.to_be_bytes());
+        buf[28] = self.roles;
+        buf
+    }
+
+    fn decode(buf: &[u8; ENTRY_SIZE]) -> Self {
+        Self {
+            address: Address::from_slice(&buf[..20]),
+            tx_num: u64::from_be_bytes(buf[20..28].try_into().expect("8 bytes")),
+            roles: buf[28],
+        }
+    }
+
+    fn key(&self) -> (Address, u64) {
+        (self.address, self.tx_num)
+    }
+}
+
+/// 一个已排序的 run 文件，覆盖连续的区块区间
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexRun {
+    pub file: String,
+    pub first_block: u64,
+    pub last_block: u64,
+    pub entries: u64,
+}
+
+/// 索引目录清单：run 按区块顺序排列，互不重叠
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexManifest {
+    pub version: u32,
+    pub chain_id: u64,
+    /// 已完整索引的最后一个区块
+    pub last_block: Option<u64>,
+    pub runs: Vec<IndexRun>,
+}
+
+/// 一次增量更新的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct IndexUpdate {
+    pub from_block: u64,
+    pub to_block: Option<u64>,
+    pub entries: u64,
+    pub runs: usize,
+}
+
+impl Record for IndexUpdate {
+    fn text(&self) -> String {
+        match self.to_block {
+            Some(to) => format!(
+                "indexed blocks {}..={}: {} entries in {} new run(s)",
+                self.from_block, to, self.entries, self.runs
+            ),
+            None => "address index is up to date".to_string(),
+        }
+    }
+}
+
+/// 本地地址 -> 交易索引（LSM 式：每次增量更新追加排序好的 run 文件）
+#[derive(Debug)]
+pub struct AddressIndex {
+    dir: PathBuf,
+    manifest: IndexManifest,
+}
+
+impl AddressIndex {
+    /// 打开（不存在则创建）索引目录；链 ID 或格式版本不符时报错
+    pub fn open(dir: impl Into<PathBuf>, chain_id: u64) -> Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir).with_context(|| format!("create index dir {}", dir.display()))?;
+        let path = dir.join(MANIFEST_FILE);
+        let manifest = if path.exists() {
+            let raw = std::fs::read_to_string(&path).with_context(|| format!("read {}", path.display()))?;
+            let manifest: IndexManifest =
+                serde_json::from_str(&raw).with_context(|| format!("parse {}", path.display()))?;
+            if manifest.version != INDEX_VERSION {
+                return Err(AppError::InvalidArg(format!(
+                    "address index {} has version {}, expected {INDEX_VERSION}; rebuild it",
+                    dir.display(),
+                    manifest.version
+                )));
+            }
+            if manifest.chain_id != chain_id {
+                return Err(AppError::InvalidArg(format!(
+                    "address index {} was built for chain {}, not {chain_id}",
+                    dir.display(),
+                    manifest.chain_id
+                )));
+            }
+            manifest
+        } else {
+            IndexManifest { version: INDEX_VERSION, chain_id, last_block: None, runs: Vec::new() }
+        };
+        Ok(Self { dir, manifest })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn manifest(&self) -> &IndexManifest {
+        &self.manifest
+    }
+
+    /// 将索引推进到 `to_block`（含）；首次构建从 `from_block` 开始
+    pub fn update(
+        &mut self,
+        db: &BscDatabase,
+        from_block: u64,
+        to_block: u64,
+        engine: &ParallelScan,
+    ) -> Result<IndexUpdate> {
+        let start = match self.manifest.last_block {
+            Some(last) => last + 1,
+            None => from_block.max(db.earliest_available_block),
+        };
+        let end = to_block.min(db.latest_block) + 1;
+        if start >= end {
+            return Ok(IndexUpdate { from_block: start, to_block: None, entries: 0, runs: 0 });
+        }
+        tracing::info!(start, end, dir = %self.dir.display(), "Updating address index");
+
+        let mut buffer: Vec<IndexEntry> = Vec::new();
+        let mut buffer_start = start;
+        let (mut entries, mut runs) = (0u64, 0usize);
+        engine.run(db, start..end, &AddressIndexScanner, |chunk, found| {
+            buffer.extend(found);
+            if buffer.len() >= FLUSH_ENTRIES {
+                entries += self.flush(&mut buffer, buffer_start, chunk.end - 1)?;
+                runs += 1;
+                buffer_start = chunk.end;
+            }
+            Ok(())
+        })?;
+        if !buffer.is_empty() {
+            entries += self.flush(&mut buffer, buffer_start, end - 1)?;
+            runs += 1;
+        } else {
+            self.manifest.last_block = Some(end - 1);
+            self.save_manifest()?;
+        }
+        Ok(IndexUpdate { from_block: start, to_block: Some(end - 1), entries, runs })
+    }
+
+    /// 排序、合并角色后写出 run 文件，再原子更新清单
+    fn flush(&mut self, buffer: &mut Vec<IndexEntry>, first_block: u64, last_block: u64) -> Result<u64> {
+        buffer.sort_unstable_by_key(IndexEntry::key);
+        buffer.dedup_by(|next, prev| {
+            let same = next.key() == prev.key();
+            if same {
+                prev.roles |= next.roles;
+            }
+            same
+        });
+
+        let file = format!("run-{first_block:010}-{last_block:010}.idx");
+        let path = self.dir.join(&file);
+        let tmp = path.with_extension("tmp");
+        {
+            let mut w = BufWriter::new(File::create(&tmp).with_context(|| format!("create {}", tmp.display()))?);
+            for entry in buffer.iter() {
+                w.write_all(&entry.encode())?;
+            }
+            w.flush()?;
+        }
+        std::fs::rename(&tmp, &path).with_context(|| format!("rename to {}", path.display()))?;
+
+        let entries = buffer.len() as u64;
+        self.manifest.runs.push(IndexRun { file, first_block, last_block, entries });
+        self.manifest.last_block = Some(last_block);
+        self.save_manifest()?;
+        tracing::info!(first_block, last_block, entries, "Address index run written");
+        buffer.clear();
+        Ok(entries)
+    }
+
+    fn save_manifest(&self) -> Result<()> {
+        let path = self.dir.join(MANIFEST_FILE);
+        let tmp = path.with_extension("tmp");
+        let json = serde_json::to_vec_pretty(&self.manifest).context("serialize address index manifest")?;
+        std::fs::write(&tmp, json).with_context(|| format!("write {}", tmp.display()))?;
+        std::fs::rename(&tmp, &path).with_context(|| format!("rename to {}", path.display()))?;
+        Ok(())
+    }
+
+    /// 按 tx 编号升序返回 `address` 的交易；`after` 为上一页最后的 tx 编号（游标）
+    pub fn query(&self, address: Address, roles: u8, after: Option<u64>, limit: usize) -> Result<Vec<IndexEntry>> {
+        let from_tx = after.map_or(0, |t| t + 1);
+        let mut found = Vec::new();
+        for run in &self.manifest.runs {
+            if found.len() >= limit {
+                break;
+            }
+            let path = self.dir.join(&run.file);
+            let mut file = File::open(&path).with_context(|| format!("open {}", path.display()))?;
+            let len = file.metadata()?.len();
+            if len % ENTRY_SIZE as u64 != 0 {
+                return Err(AppError::Corrupt(format!("address index run {} has a partial record", path.display())));
+            }
+            let start = lower_bound(&mut file, len / ENTRY_SIZE as u64, (address, from_tx))?;
+            file.seek(SeekFrom::Start(start * ENTRY_SIZE as u64))?;
+            let mut reader = BufReader::new(file);
+            let mut buf = [0u8; ENTRY_SIZE];
+            for _ in start..len / ENTRY_SIZE as u64 {
+                reader.read_exact(&mut buf)?;
+                let entry = IndexEntry::decode(&buf);
+                if entry.address != address || found.len() >= limit {
+                    break;
+                }
+                if entry.roles & roles != 0 {
+                    found.push(entry);
+                }
+            }
+        }
+        Ok(found)
+    }
+}
+
+/// 在已排序 run 中查找第一个 >= key 的记录位置
+fn lower_bound(file: &mut File, count: u64, key: (Address, u64)) -> Result<u64> {
+    let (mut lo, mut hi) = (0u64, count);
+    let mut buf = [0u8; ENTRY_SIZE];
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        file.seek(SeekFrom::Start(mid * ENTRY_SIZE as u64))?;
+        file.read_exact(&mut buf)?;
+        if IndexEntry::decode(&buf).key() < key {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+    Ok(lo)
+}
+
+/// 为区块分片生成索引条目；senders 被裁剪时恢复签名，receipts 被裁剪时不产生 log 角色
+pub struct AddressIndexScanner;
+
+impl RangeScanner for AddressIndexScanner {
+    type Output = IndexEntry;
+
+    fn scan_chunk(&self, provider: BscProvider, range: Range<u64>) -> Result<Vec<IndexEntry>> {
+        let mut entries = Vec::new();
+        let mut missing_receipts = 0u64;
+        for bundle in BlockIter::new(provider, range.start, range.end) {
+            let bundle = bundle?;
+            let senders = match bundle.senders {
+                Some(senders) => senders,
+                None => recover_senders(&bundle.transactions)?,
+            };
+            for (i, (tx, from)) in bundle.transactions.iter().zip(senders).enumerate() {
+                let tx_num = bundle.first_tx_num + i as u64;
+                entries.push(IndexEntry { address: from, tx_num, roles: AddressRole::From.bit() });
+                match tx.to() {
+                    Some(to) => entries.push(IndexEntry { address: to, tx_num, roles: AddressRole::To.bit() }),
+                    None => entries.push(IndexEntry {
+                        address: from.create(tx.nonce()),
+                        tx_num,
+                        roles: AddressRole::Created.bit(),
+                    }),
+                }
+            }
+            match &bundle.receipts {
+                Some(receipts) => {
+                    for (i, receipt) in receipts.iter().enumerate() {
+                        let tx_num = bundle.first_tx_num + i as u64;
+                        for log in &receipt.logs {
+                            entries.push(IndexEntry { address: log.address, tx_num, roles: AddressRole::Log.bit() });
+                        }
+                    }
+                }
+                None if !bundle.transactions.is_empty() => missing_receipts += 1,
+                None => {}
+            }
+        }
+        if missing_receipts > 0 {
+            tracing::warn!(start = range.start, end = range.end, blocks = missing_receipts, "Receipts pruned; log emitters not indexed");
+        }
+        Ok(entries)
+    }
+}
+
+/// address-txs 输出
+#[derive(Debug, Clone, Serialize)]
+pub struct AddressTxRow {
+    pub tx_num: u64,
+    pub block_number: Option<u64>,
+    pub tx_index: Option<u64>,
+    /// 交易体被裁剪时为 None
+    pub hash: Option<B256>,
+    pub roles: String,
+}
+
+impl Record for AddressTxRow {
+    fn text(&self) -> String {
+        format!(
+            "tx_num={} block={} index={} hash={} roles={}",
+            self.tx_num,
+            self.block_number.map(|b| b.to_string()).unwrap_or_else(|| "-".to_string()),
+            self.tx_index.map(|i| i.to_string()).unwrap_or_else(|| "-".to_string()),
+            self.hash.map(|h| format!("{h:#x}")).unwrap_or_else(|| "-".to_string()),
+            self.roles,
+        )
+    }
+}
+
+/// 将索引条目补全为区块号、区块内序号与交易哈希
+pub fn resolve_entries(db: &BscDatabase, entries: &[IndexEntry]) -> Result<Vec<AddressTxRow>> {
+    let provider = db.provider()?;
+    entries
+        .iter()
+        .map(|entry| {
+            let block_number = provider.transaction_block(entry.tx_num)?;
+            let tx_index = match block_number {
+                Some(block) => provider
+                    .block_body_indices(block)?
+                    .map(|body| entry.tx_num - body.first_tx_num()),
+                None => None,
+            };
+            let hash = provider.transaction_by_id(entry.tx_num)?.map(|tx| *tx.hash());
+            Ok(AddressTxRow {
+                tx_num: entry.tx_num,
+                block_number,
+                tx_index,
+                hash,
+                roles: AddressRole::describe(entry.roles),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{temp_dir, BUSD, ROUTER, WBNB};
+
+    fn entry(address: Address, tx_num: u64, roles: &[AddressRole]) -> IndexEntry {
+        IndexEntry { address, tx_num, roles: AddressRole::mask(roles) }
+    }
+
+    #[test]
+    fn empty_role_list_selects_every_role() {
+        assert_eq!(AddressRole::describe(AddressRole::mask(&[])), "from|to|created|log");
+        assert_eq!(AddressRole::describe(AddressRole::mask(&[AddressRole::Log, AddressRole::From])), "from|log");
+        assert_eq!(AddressRole::describe(0), "");
+    }
+
+    #[test]
+    fn entry_encoding_sorts_by_address_then_tx_num() {
+        let e = entry(WBNB, 0x0102_0304_0506_0708, &[AddressRole::From, AddressRole::Log]);
+        let buf = e.encode();
+        assert_eq!(&buf[..20], WBNB.as_slice());
+        assert_eq!(&buf[20..28], &[1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(buf[28], AddressRole::From.bit() | AddressRole::Log.bit());
+        assert_eq!(IndexEntry::decode(&buf), e);
+
+        // 大端 tx 编号使字节序与 (address, tx_num) 的排序一致
+        let a = entry(WBNB, 255, &[AddressRole::To]).encode();
+        let b = entry(WBNB, 256, &[AddressRole::To]).encode();
+        let c = entry(BUSD, 0, &[AddressRole::To]).encode();
+        assert!(a < b && b < c);
+    }
+
+    #[test]
+    fn lower_bound_finds_first_entry_not_less_than_key() {
+        let dir = temp_dir("address-index-lower-bound");
+        let path = dir.join("run.idx");
+        let entries = [
+            entry(ROUTER, 7, &[AddressRole::To]),
+            entry(WBNB, 3, &[AddressRole::Log]),
+            entry(WBNB, 9, &[AddressRole::Log]),
+            entry(WBNB, 12, &[AddressRole::From]),
+            entry(BUSD, 1, &[AddressRole::Log]),
+        ];
+        let raw: Vec<u8> = entries.iter().flat_map(|e| e.encode()).collect();
+        std::fs::write(&path, raw).unwrap();
+
+        let mut file = File::open(&path).unwrap();
+        let count = entries.len() as u64;
+        assert_eq!(lower_bound(&mut file, count, (WBNB, 0)).unwrap(), 1);
+        assert_eq!(lower_bound(&mut file, count, (WBNB, 9)).unwrap(), 2);
+        assert_eq!(lower_bound(&mut file, count, (WBNB, 10)).unwrap(), 3);
+        assert_eq!(lower_bound(&mut file, count, (BUSD, 2)).unwrap(), count);
+        assert_eq!(lower_bound(&mut file, count, (Address::ZERO, 0)).unwrap(), 0);
+        assert_eq!(lower_bound(&mut file, 0, (WBNB, 0)).unwrap(), 0);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn query_merges_roles_filters_and_pages_across_runs() {
+        let dir = temp_dir("address-index-query");
+        let mut index = AddressIndex::open(&dir, 56).unwrap();
+        let mut first = vec![
+            entry(WBNB, 10, &[AddressRole::To]),
+            entry(ROUTER, 10, &[AddressRole::To]),
+            entry(WBNB, 10, &[AddressRole::Log]),
+            entry(WBNB, 4, &[AddressRole::Log]),
+        ];
+        let mut second = vec![entry(WBNB, 25, &[AddressRole::From]), entry(BUSD, 25, &[AddressRole::Log])];
+        index.flush(&mut first, 100, 199).unwrap();
+        index.flush(&mut second, 200, 299).unwrap();
+
+        let all = index.query(WBNB, AddressRole::mask(&[]), None, 10).unwrap();
+        assert_eq!(all.iter().map(|e| e.tx_num).collect::<Vec<_>>(), [4, 10, 25]);
+        assert_eq!(all[1].roles, AddressRole::To.bit() | AddressRole::Log.bit());
+
+        let from = index.query(WBNB, AddressRole::mask(&[AddressRole::From]), None, 10).unwrap();
+        assert_eq!(from.iter().map(|e| e.tx_num).collect::<Vec<_>>(), [25]);
+
+        // 游标是上一页最后的 tx 编号，下一页从它之后开始
+        let page = index.query(WBNB, AddressRole::mask(&[]), Some(4), 1).unwrap();
+        assert_eq!(page.iter().map(|e| e.tx_num).collect::<Vec<_>>(), [10]);
+
+        let reopened = AddressIndex::open(&dir, 56).unwrap();
+        assert_eq!(reopened.manifest().last_block, Some(299));
+        assert_eq!(reopened.manifest().runs.len(), 2);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn open_rejects_other_chain_and_version() {
+        let dir = temp_dir("address-index-manifest");
+        let mut index = AddressIndex::open(&dir, 56).unwrap();
+        index.flush(&mut vec![entry(WBNB, 1, &[AddressRole::From])], 0, 9).unwrap();
+        assert!(matches!(AddressIndex::open(&dir, 97), Err(AppError::InvalidArg(_))));
+
+        index.manifest.version = INDEX_VERSION + 1;
+        index.save_manifest().unwrap();
+        assert!(matches!(AddressIndex::open(&dir, 56), Err(AppError::InvalidArg(_))));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn query_rejects_run_with_partial_record() {
+        let dir = temp_dir("address-index-partial");
+        let mut index = AddressIndex::open(&dir, 56).unwrap();
+        index.flush(&mut vec![entry(WBNB, 1, &[AddressRole::From])], 0, 9).unwrap();
+        let run = dir.join(&index.manifest().runs[0].file);
+        let mut raw = std::fs::read(&run).unwrap();
+        raw.pop();
+        std::fs::write(&run, raw).unwrap();
+
+        assert!(matches!(index.query(WBNB, AddressRole::mask(&[]), None, 10), Err(AppError::Corrupt(_))));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,16 +1,41 @@
-use alloy_primitives::{Address, B256, U256};
+use alloy_primitives::{Address, Selector, B256, U256};
 use alloy_consensus::transaction::Transaction; // bring nonce/gas_limit/to/value/input/gas_price APIs into scope
-use eyre::Result;
-use reth_primitives::TransactionSigned;
+use alloy_consensus::transaction::SignerRecoverable; // recover_signer()
+use reth_ethereum_primitives::Receipt;
+use std::ops::Range;
 
-use crate::databases::BscDatabase;
+use reth_primitives::{Header, TransactionSigned};
+use serde::Serialize;
+
+use crate::blocks::{BlockBundle, BlockIter};
+use crate::checkpoint::Checkpointer;
+use crate::databases::{BscDatabase, BscProvider};
+use crate::error::{AppError, Result};
+use crate::output::Record;
+use crate::parallel::{ParallelScan, RangeScanner};
+use crate::system;
+
+/// 少于该数量的交易直接串行恢复签名
+const PARALLEL_RECOVERY_THRESHOLD: usize = 64;
+
+/// 发送者地址来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SenderSource {
+	/// 节点存储的 TransactionSenders（MDBX 或 static_files）
+	Stored,
+	/// senders 已被裁剪，由签名 ECDSA 恢复
+	Recovered,
+}
 
 /// 可分析的交易摘要结构
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct AnalyzedTx {
 	pub block_number: u64,
 	pub index: u32,
 	pub hash: B256,
+	pub from: Address,
+	pub sender_source: SenderSource,
 	pub to: Option<Address>,
 	pub value: U256,
 	pub nonce: u64,
@@ -19,14 +44,113 @@ pub struct AnalyzedTx {
 	pub max_fee_per_gas: Option<U256>,          // EIP-1559/4844
 	pub max_priority_fee_per_gas: Option<U256>, // EIP-1559/4844
 	pub input_size: usize,
+	/// calldata 前 4 字节（不足 4 字节为 None）
+	pub selector: Option<Selector>,
 	pub tx_type: &'static str,
+	/// BSC 系统交易命中的系统合约与函数（普通交易为 None）
+	pub system_contract: Option<&'static str>,
+	pub system_function: Option<&'static str>,
+	/// 实际单价：min(max_fee, base_fee + priority_fee)，Legacy 即 gas_price
+	pub effective_gas_price: U256,
+	/// 以下字段来自回执，receipts 被裁剪时为 None
+	pub gas_used: Option<u64>, // 相邻 cumulative_gas_used 之差
+	pub fee: Option<U256>,     // gas_used * effective_gas_price
+	pub success: Option<bool>,
+}
+
+/// 交易类型名称（与 AnalyzedTx::tx_type 一致）
+pub fn tx_type_name(tx: &TransactionSigned) -> &'static str {
+	match tx {
+		TransactionSigned::Legacy(_) => "Legacy",
+		TransactionSigned::Eip2930(_) => "Eip2930",
+		TransactionSigned::Eip1559(_) => "Eip1559",
+		TransactionSigned::Eip4844(_) => "Eip4844",
+		TransactionSigned::Eip7702(_) => "Eip7702",
+	}
 }
 
-/// 将 Vec<TransactionSigned> 转换为可分析的列表
-pub fn analyze_txs(block_number: u64, txs: Vec<TransactionSigned>) -> Vec<AnalyzedTx> {
-	txs.into_iter()
+/// 并行恢复一组交易的发送者（按 CPU 核数分块）
+pub fn recover_senders(txs: &[TransactionSigned]) -> Result<Vec<Address>> {
+	let recover = |tx: &TransactionSigned| {
+		tx.recover_signer()
+			.map_err(|e| AppError::Corrupt(format!("recover signer of tx {}: {e}", tx.hash())))
+	};
+	if txs.len() < PARALLEL_RECOVERY_THRESHOLD {
+		return txs.iter().map(recover).collect();
+	}
+
+	let threads = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+	let chunk = txs.len().div_ceil(threads);
+	std::thread::scope(|scope| {
+		let handles: Vec<_> = txs
+			.chunks(chunk)
+			.map(|part| scope.spawn(move || part.iter().map(recover).collect::<Result<Vec<_>>>()))
+			.collect();
+		let mut senders = Vec::with_capacity(txs.len());
+		for handle in handles {
+			let part = handle
+				.join()
+				.map_err(|_| AppError::Corrupt("sender recovery thread panicked".to_string()))??;
+			senders.extend(part);
+		}
+		Ok(senders)
+	})
+}
+
+/// 由累计 gas 推算每笔交易的 gas_used，同时带出成功标记
+fn receipt_gas(block_number: u64, tx_count: usize, receipts: Option<Vec<Receipt>>) -> Result<Vec<Option<(u64, bool)>>> {
+	let Some(receipts) = receipts else {
+		return Ok(vec![None; tx_count]);
+	};
+	if receipts.len() != tx_count {
+		return Err(AppError::Corrupt(format!(
+			"block {block_number}: {tx_count} transactions but {} receipts",
+			receipts.len()
+		)));
+	}
+	let mut prev = 0u64;
+	receipts
+		.iter()
+		.map(|r| {
+			let used = r.cumulative_gas_used.checked_sub(prev).ok_or_else(|| {
+				AppError::Corrupt(format!("block {block_number}: cumulative_gas_used decreases"))
+			})?;
+			prev = r.cumulative_gas_used;
+			Ok(Some((used, r.success)))
+		})
+		.collect()
+}
+
+impl AnalyzedTx {
+	/// 是否为 BSC 系统交易
+	pub fn is_system(&self) -> bool {
+		self.system_contract.is_some()
+	}
+}
+
+/// 将 Vec<TransactionSigned> 转换为可分析的列表。
+/// `senders` 为节点存储的发送者；为 None（已裁剪）或数量不符时回退到签名恢复。
+/// `receipts` 为 None（已裁剪）时回执相关字段留空。
+/// 区块头提供 base fee（计算实际单价）与 coinbase（识别系统交易）。
+pub fn analyze_txs(
+	header: &Header,
+	txs: Vec<TransactionSigned>,
+	senders: Option<Vec<Address>>,
+	receipts: Option<Vec<Receipt>>,
+) -> Result<Vec<AnalyzedTx>> {
+	let block_number = header.number;
+	let receipt_gas = receipt_gas(block_number, txs.len(), receipts)?;
+	let (senders, sender_source) = match senders {
+		Some(s) if s.len() == txs.len() => (s, SenderSource::Stored),
+		_ => (recover_senders(&txs)?, SenderSource::Recovered),
+	};
+	tracing::debug!(block_number, txs = txs.len(), ?sender_source, "Resolved senders");
+
+	let analyzed = txs.into_iter()
+		.zip(senders)
+		.zip(receipt_gas)
 		.enumerate()
-		.map(|(idx, tx)| {
+		.map(|(idx, ((tx, from), receipt))| {
 			// 通用字段
 			let hash = tx.hash();
 			let nonce = tx.nonce();
@@ -34,39 +158,36 @@ pub fn analyze_txs(block_number: u64, txs: Vec<TransactionSigned>) -> Vec<Analyz
 			let to = tx.to();
 			let value = tx.value();
 			let input_size = tx.input().len();
+			let selector = tx.input().get(..4).map(Selector::from_slice);
+			let effective_gas_price = U256::from(tx.effective_gas_price(header.base_fee_per_gas));
+			let system = system::classify(header.beneficiary, from, to, effective_gas_price, selector);
 
-			// 费用与类型因交易变体而异
-			let (gas_price, max_fee_per_gas, max_priority_fee_per_gas, tx_type) = match &tx {
+			// 费用字段因交易变体而异
+			let (gas_price, max_fee_per_gas, max_priority_fee_per_gas) = match &tx {
 				TransactionSigned::Legacy(t) => (
 					t.gas_price().map(U256::from),
 					None,
 					None,
-					"Legacy",
 				),
 				TransactionSigned::Eip2930(t) => (
 					t.gas_price().map(U256::from),
 					None,
 					None,
-					"Eip2930",
 				),
 				TransactionSigned::Eip1559(t) => (
 					None,
 					Some(U256::from(t.max_fee_per_gas())),
 					t.max_priority_fee_per_gas().map(U256::from),
-					"Eip1559",
 				),
 				TransactionSigned::Eip4844(t) => (
 					None,
 					Some(U256::from(t.max_fee_per_gas())),
 					t.max_priority_fee_per_gas().map(U256::from),
-					"Eip4844",
 				),
-				#[allow(unused)]
 				TransactionSigned::Eip7702(t) => (
 					None,
 					Some(U256::from(t.max_fee_per_gas())),
 					t.max_priority_fee_per_gas().map(U256::from),
-					"Eip7702",
 				),
 			};
 
@@ -74,6 +195,8 @@ pub fn analyze_txs(block_number: u64, txs: Vec<TransactionSigned>) -> Vec<Analyz
 				block_number,
 				index: idx as u32,
 				hash: *hash,
+				from,
+				sender_source,
 				to,
 				value,
 				nonce,
@@ -82,16 +205,43 @@ pub fn analyze_txs(block_number: u64, txs: Vec<TransactionSigned>) -> Vec<Analyz
 				max_fee_per_gas,
 				max_priority_fee_per_gas,
 				input_size,
-				tx_type,
+				selector,
+				tx_type: tx_type_name(&tx),
+				system_contract: system.map(|s| s.contract),
+				system_function: system.and_then(|s| s.function),
+				effective_gas_price,
+				gas_used: receipt.map(|(used, _)| used),
+				fee: receipt.map(|(used, _)| effective_gas_price * U256::from(used)),
+				success: receipt.map(|(_, ok)| ok),
 			}
 		})
-		.collect()
+		.collect();
+	Ok(analyzed)
+}
+
+/// 被裁剪的数据视为缺失，其余错误照常返回
+fn unless_pruned<T>(result: Result<T>) -> Result<Option<T>> {
+	match result {
+		Ok(v) => Ok(Some(v)),
+		Err(e) if e.is_pruned() => Ok(None),
+		Err(e) => Err(e),
+	}
 }
 
-/// 入口：读取区块交易并转换为 AnalyzedTx
+/// 入口：读取区块头、交易、发送者与回执并转换为 AnalyzedTx
 pub fn analyze_block(db: &BscDatabase, block_number: u64) -> Result<Vec<AnalyzedTx>> {
+	let header = db
+		.query_headers_with_blocknumber(block_number)?
+		.ok_or_else(|| AppError::NotFound(format!("header of block {block_number}")))?;
 	let txs = db.query_block_order_transactions(block_number)?;
-	Ok(analyze_txs(block_number, txs))
+	let senders = unless_pruned(db.query_block_senders(block_number))?;
+	let receipts = unless_pruned(db.query_block_receipts(block_number))?;
+	analyze_txs(&header.header, txs, senders, receipts)
+}
+
+/// 流式版本：直接使用 BlockIter 产出的数据包
+pub fn analyze_bundle(bundle: BlockBundle) -> Result<Vec<AnalyzedTx>> {
+	analyze_txs(&bundle.header, bundle.transactions, bundle.senders, bundle.receipts)
 }
 
 pub fn analyze_block_transactions_with_to(
@@ -102,3 +252,196 @@ pub fn analyze_block_transactions_with_to(
 	let addrs: Vec<Option<Address>> = txs.into_iter().map(|tx| tx.to()).collect();
 	Ok(addrs)
 }
+
+/// 排除系统交易后的 (区块内序号, to)
+pub fn user_transactions_with_to(db: &BscDatabase, block_number: u64) -> Result<Vec<(usize, Option<Address>)>> {
+	let txs = analyze_block(db, block_number)?;
+	Ok(txs.into_iter().filter(|t| !t.is_system()).map(|t| (t.index as usize, t.to)).collect())
+}
+
+impl Record for AnalyzedTx {
+	fn text(&self) -> String {
+		let opt = |v: Option<String>| v.unwrap_or_else(|| "-".to_string());
+		format!(
+			"{:>6} #{:<4} {:#x} {:<8} from={:#x} to={} value={} gas={}/{} price={} fee={} status={}{}",
+			self.block_number,
+			self.index,
+			self.hash,
+			self.tx_type,
+			self.from,
+			opt(self.to.map(|a| format!("{a:#x}"))),
+			self.value,
+			opt(self.gas_used.map(|g| g.to_string())),
+			self.gas_limit,
+			self.effective_gas_price,
+			opt(self.fee.map(|f| f.to_string())),
+			match self.success {
+				Some(true) => "ok",
+				Some(false) => "failed",
+				None => "-",
+			},
+			match (self.system_contract, self.system_function) {
+				(Some(c), Some(f)) => format!(" system={c}.{f}"),
+				(Some(c), None) => format!(" system={c}"),
+				_ => String::new(),
+			},
+		)
+	}
+}
+
+/// 输入数据大小分桶上界：空调用 / 简单调用（如 transfer 为 68 字节）/ 中等 / 大
+const INPUT_SMALL_MAX: usize = 68;
+const INPUT_MEDIUM_MAX: usize = 1024;
+
+/// 单区块聚合统计（字段平铺，便于 CSV 输出）
+#[derive(Debug, Clone, Serialize)]
+pub struct BlockStats {
+	pub block_number: u64,
+	pub timestamp: u64,
+	/// 参与统计的交易数（排除系统交易时不含系统交易）
+	pub tx_count: usize,
+	pub system_txs: usize,
+	pub gas_used: u64,
+	pub gas_limit: u64,
+	/// gas_used / gas_limit
+	pub gas_utilization: f64,
+	// 交易类型分布
+	pub legacy: usize,
+	pub eip2930: usize,
+	pub eip1559: usize,
+	pub eip4844: usize,
+	pub eip7702: usize,
+	// effective_gas_price 分布（空块为 None）
+	pub min_gas_price: Option<U256>,
+	pub median_gas_price: Option<U256>,
+	pub p90_gas_price: Option<U256>,
+	pub contract_creations: usize,
+	// 输入数据大小分布
+	pub input_empty: usize,
+	pub input_small: usize,
+	pub input_medium: usize,
+	pub input_large: usize,
+	pub input_max: usize,
+}
+
+/// 最近秩百分位（`sorted` 已升序）
+fn percentile<T: Copy>(sorted: &[T], p: f64) -> Option<T> {
+	if sorted.is_empty() {
+		return None;
+	}
+	let rank = (p * sorted.len() as f64).ceil() as usize;
+	Some(sorted[rank.clamp(1, sorted.len()) - 1])
+}
+
+/// 由区块头与该块的 AnalyzedTx 计算聚合统计；`exclude_system` 时系统交易只计入 system_txs
+pub fn block_stats(header: &Header, txs: &[AnalyzedTx], exclude_system: bool) -> BlockStats {
+	let system_txs = txs.iter().filter(|t| t.is_system()).count();
+	let txs: Vec<&AnalyzedTx> = txs.iter().filter(|t| !(exclude_system && t.is_system())).collect();
+	let mut prices: Vec<U256> = txs.iter().map(|t| t.effective_gas_price).collect();
+	prices.sort_unstable();
+	let count_type = |ty: &str| txs.iter().filter(|t| t.tx_type == ty).count();
+	let count_input = |f: &dyn Fn(usize) -> bool| txs.iter().filter(|t| f(t.input_size)).count();
+
+	BlockStats {
+		block_number: header.number,
+		timestamp: header.timestamp,
+		tx_count: txs.len(),
+		system_txs,
+		gas_used: header.gas_used,
+		gas_limit: header.gas_limit,
+		gas_utilization: if header.gas_limit == 0 {
+			0.0
+		} else {
+			header.gas_used as f64 / header.gas_limit as f64
+		},
+		legacy: count_type("Legacy"),
+		eip2930: count_type("Eip2930"),
+		eip1559: count_type("Eip1559"),
+		eip4844: count_type("Eip4844"),
+		eip7702: count_type("Eip7702"),
+		min_gas_price: prices.first().copied(),
+		median_gas_price: percentile(&prices, 0.5),
+		p90_gas_price: percentile(&prices, 0.9),
+		contract_creations: txs.iter().filter(|t| t.to.is_none()).count(),
+		input_empty: count_input(&|n| n == 0),
+		input_small: count_input(&|n| (1..=INPUT_SMALL_MAX).contains(&n)),
+		input_medium: count_input(&|n| (INPUT_SMALL_MAX + 1..=INPUT_MEDIUM_MAX).contains(&n)),
+		input_large: count_input(&|n| n > INPUT_MEDIUM_MAX),
+		input_max: txs.iter().map(|t| t.input_size).max().unwrap_or_default(),
+	}
+}
+
+impl Record for BlockStats {
+	fn text(&self) -> String {
+		let opt = |v: Option<U256>| v.map(|p| p.to_string()).unwrap_or_else(|| "-".to_string());
+		format!(
+			"{:>10} txs={:<4} system={:<2} util={:>6.2}% types(L/A/D/B/S)={}/{}/{}/{}/{} price(min/med/p90)={}/{}/{} creates={} input(0/s/m/l)={}/{}/{}/{} max={}",
+			self.block_number,
+			self.tx_count,
+			self.system_txs,
+			self.gas_utilization * 100.0,
+			self.legacy,
+			self.eip2930,
+			self.eip1559,
+			self.eip4844,
+			self.eip7702,
+			opt(self.min_gas_price),
+			opt(self.median_gas_price),
+			opt(self.p90_gas_price),
+			self.contract_creations,
+			self.input_empty,
+			self.input_small,
+			self.input_medium,
+			self.input_large,
+			self.input_max,
+		)
+	}
+}
+
+/// 单区块分析结果
+#[derive(Debug, Clone)]
+pub struct BlockAnalysis {
+	pub stats: BlockStats,
+	pub txs: Vec<AnalyzedTx>,
+}
+
+/// 在区块分片内逐块生成 AnalyzedTx 与聚合统计
+pub struct AnalyzeScanner {
+	/// 从交易行与统计中排除 BSC 系统交易
+	pub exclude_system: bool,
+}
+
+impl RangeScanner for AnalyzeScanner {
+	type Output = BlockAnalysis;
+
+	fn scan_chunk(&self, provider: BscProvider, range: Range<u64>) -> Result<Vec<BlockAnalysis>> {
+		BlockIter::new(provider, range.start, range.end)
+			.map(|bundle| {
+				let bundle = bundle?;
+				let header = bundle.header.clone();
+				let mut txs = analyze_bundle(bundle)?;
+				let stats = block_stats(&header, &txs, self.exclude_system);
+				if self.exclude_system {
+					txs.retain(|t| !t.is_system());
+				}
+				Ok(BlockAnalysis { stats, txs })
+			})
+			.collect()
+	}
+}
+
+/// 并行分析区块范围，按区块顺序回调（检查点语义同 scan_ct）
+pub fn analyze_range(
+	db: &BscDatabase,
+	range: Range<u64>,
+	engine: &ParallelScan,
+	exclude_system: bool,
+	checkpointer: Option<&Checkpointer>,
+	resume: bool,
+	mut on_block: impl FnMut(BlockAnalysis) -> Result<()>,
+) -> Result<()> {
+	let scanner = AnalyzeScanner { exclude_system };
+	engine.run_checkpointed(db, range, &scanner, checkpointer, resume, |_, blocks| {
+		blocks.into_iter().try_for_each(&mut on_block)
+	})
+}
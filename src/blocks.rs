@@ -0,0 +1,171 @@
+use std::collections::VecDeque;
+use std::ops::{Bound, RangeBounds};
+
+use alloy_primitives::{Address, B256};
+use reth_ethereum_primitives::Receipt;
+use reth_primitives::{Header, TransactionSigned};
+use reth_provider::{
+    BlockBodyIndicesProvider, BlockHashReader, HeaderProvider, ReceiptProvider, TransactionsProvider,
+};
+
+use crate::databases::{BscDatabase, BscProvider};
+use crate::error::{AppError, Result};
+
+/// 每批从存储中读取的区块数
+pub const DEFAULT_BATCH_SIZE: u64 = 256;
+
+/// 单个区块的完整数据包
+#[derive(Debug, Clone)]
+pub struct BlockBundle {
+    pub number: u64,
+    pub hash: B256,
+    pub header: Header,
+    /// 区块内第一笔交易的全局 tx 编号
+    pub first_tx_num: u64,
+    pub transactions: Vec<TransactionSigned>,
+    /// None 表示 senders 已被裁剪
+    pub senders: Option<Vec<Address>>,
+    /// None 表示 receipts 已被裁剪
+    pub receipts: Option<Vec<Receipt>>,
+}
+
+/// 顺序遍历区块范围的迭代器。
+///
+/// 每批使用 provider 的 `*_range` 接口读取：static_files 段内走 jar 游标，
+/// MDBX 尾部走表游标，避免逐块逐 tx 的随机查找。
+pub struct BlockIter {
+    provider: BscProvider,
+    next: u64,
+    end: u64,
+    batch_size: u64,
+    buf: VecDeque<BlockBundle>,
+}
+
+impl BlockIter {
+    /// 创建迭代器；`end` 为开区间
+    pub fn new(provider: BscProvider, start: u64, end: u64) -> Self {
+        Self { provider, next: start, end, batch_size: DEFAULT_BATCH_SIZE, buf: VecDeque::new() }
+    }
+
+    /// 调整每批读取的区块数（至少为 1）
+    pub fn with_batch_size(mut self, batch_size: u64) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    fn fill(&mut self) -> Result<()> {
+        let start = self.next;
+        let end = start.saturating_add(self.batch_size).min(self.end);
+        self.next = end;
+
+        let headers = self.provider.headers_range(start..end)?;
+        let hashes = self.provider.canonical_hashes_range(start, end)?;
+        let indices = self
+            .provider
+            .block_body_indices_range(start..=end - 1)?;
+        let expected = (end - start) as usize;
+        if headers.len() != expected || hashes.len() != expected || indices.len() != expected {
+            return Err(AppError::Corrupt(format!(
+                "incomplete block data for {start}..{end}: headers={} hashes={} bodies={}",
+                headers.len(),
+                hashes.len(),
+                indices.len()
+            )));
+        }
+
+        let tx_start = indices.first().map(|i| i.first_tx_num()).unwrap_or_default();
+        let tx_end = indices.last().map(|i| i.first_tx_num() + i.tx_count()).unwrap_or(tx_start);
+        let tx_range = tx_start..tx_end;
+        let tx_total = (tx_end - tx_start) as usize;
+
+        let mut transactions = self
+            .provider
+            .transactions_by_tx_range(tx_range.clone())?
+            .into_iter();
+        if transactions.len() != tx_total {
+            return Err(AppError::Corrupt(format!(
+                "incomplete transactions for {start}..{end}: expected {tx_total}, got {}",
+                transactions.len()
+            )));
+        }
+        let senders = self.provider.senders_by_tx_range(tx_range.clone())?;
+        let receipts = self.provider.receipts_by_tx_range(tx_range)?;
+        if senders.len() > tx_total || receipts.len() > tx_total {
+            return Err(AppError::Corrupt(format!(
+                "too many senders/receipts for {start}..{end}: txs={tx_total} senders={} receipts={}",
+                senders.len(),
+                receipts.len()
+            )));
+        }
+
+        // 整批齐全时按位置一一对应；否则（裁剪边界落在区块中间，或 receipts 按日志过滤稀疏裁剪）
+        // 逐块按其 tx 编号区间重新读取，条数不齐的区块视为已裁剪
+        let mut senders = (senders.len() == tx_total).then(|| senders.into_iter());
+        let mut receipts = (receipts.len() == tx_total).then(|| receipts.into_iter());
+
+        for ((header, hash), body) in headers.into_iter().zip(hashes).zip(indices) {
+            let first_tx_num = body.first_tx_num();
+            let count = body.tx_count() as usize;
+            let txs: Vec<_> = transactions.by_ref().take(count).collect();
+            let block_senders = match senders.as_mut() {
+                Some(all) => Some(all.by_ref().take(count).collect()),
+                None => complete(self.provider.senders_by_tx_range(body.tx_num_range())?, count),
+            };
+            let block_receipts = match receipts.as_mut() {
+                Some(all) => Some(all.by_ref().take(count).collect()),
+                None => complete(self.provider.receipts_by_tx_range(body.tx_num_range())?, count),
+            };
+            self.buf.push_back(BlockBundle {
+                number: header.number,
+                hash,
+                header,
+                first_tx_num,
+                transactions: txs,
+                senders: block_senders,
+                receipts: block_receipts,
+            });
+        }
+
+        tracing::debug!(start, end, txs = tx_total, "Loaded block batch");
+        Ok(())
+    }
+}
+
+/// 区块内条目与交易数一致时才视为可用，否则（部分裁剪）返回 None
+fn complete<T>(items: Vec<T>, count: usize) -> Option<Vec<T>> {
+    (items.len() == count).then_some(items)
+}
+
+impl Iterator for BlockIter {
+    type Item = Result<BlockBundle>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.buf.is_empty() && self.next < self.end {
+            if let Err(e) = self.fill() {
+                // 出错后终止迭代
+                self.next = self.end;
+                return Some(Err(e));
+            }
+        }
+        self.buf.pop_front().map(Ok)
+    }
+}
+
+impl BscDatabase {
+    /// 按区块顺序流式遍历 `range`（自动裁剪到可用区间）
+    pub fn blocks(&self, range: impl RangeBounds<u64>) -> Result<BlockIter> {
+        let start = match range.start_bound() {
+            Bound::Included(&n) => n,
+            Bound::Excluded(&n) => n.saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&n) => n.saturating_add(1),
+            Bound::Excluded(&n) => n,
+            Bound::Unbounded => self.latest_block.saturating_add(1),
+        };
+        let start = start.max(self.earliest_available_block);
+        let end = end.min(self.latest_block.saturating_add(1));
+        Ok(BlockIter::new(self.provider()?, start, end.max(start)))
+    }
+}
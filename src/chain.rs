@@ -0,0 +1,168 @@
+use std::{fmt, path::PathBuf, str::FromStr, sync::Arc};
+
+use alloy_genesis::{ChainConfig, Genesis};
+use alloy_primitives::{b256, B256, U256};
+use reth_chainspec::{Chain, ChainSpec, ChainSpecBuilder, EthereumHardfork, ForkCondition};
+use reth_primitives::SealedHeader;
+
+use crate::error::{AppError, Result};
+
+/// BSC 主网 genesis 区块哈希
+pub const BSC_MAINNET_GENESIS_HASH: B256 =
+    b256!("0x0d21840abff46b96c84b2ac9e10e4f5cdaeb5693cb665db62a2f3b02d2d57b5b");
+
+/// Chapel 测试网 genesis 区块哈希
+pub const BSC_CHAPEL_GENESIS_HASH: B256 =
+    b256!("0x6d3c66c5357ec91d5c43af47e234a939b22557cbb552dc45bebbceeed90fbe34");
+
+/// 链选择：BSC 主网 / Chapel 测试网 / 自定义 genesis.json
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum BscChain {
+    #[default]
+    Bsc,
+    Chapel,
+    /// geth 格式的 genesis.json（config 中的 berlinBlock/shanghaiTime 等字段决定分叉表）
+    Custom(PathBuf),
+}
+
+impl FromStr for BscChain {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "bsc" | "mainnet" | "56" => Ok(Self::Bsc),
+            "chapel" | "testnet" | "97" => Ok(Self::Chapel),
+            // 只有看起来像路径的值才当作自定义 genesis，避免链名拼错时报“文件不存在”
+            path if path.contains('/') || path.contains(std::path::MAIN_SEPARATOR) || path.ends_with(".json") => {
+                Ok(Self::Custom(PathBuf::from(path)))
+            }
+            other => Err(AppError::InvalidArg(format!(
+                "unknown chain {other:?}; expected bsc|mainnet|56, chapel|testnet|97 or a genesis .json path"
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for BscChain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bsc => write!(f, "bsc"),
+            Self::Chapel => write!(f, "chapel"),
+            Self::Custom(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+impl BscChain {
+    /// 构建对应的 ChainSpec（链 ID + 分叉激活表），供 ProviderFactory 与 EvmConfig 共用
+    pub fn chain_spec(&self) -> Result<Arc<ChainSpec>> {
+        let spec = match self {
+            Self::Bsc => bsc_mainnet_spec(),
+            Self::Chapel => bsc_chapel_spec(),
+            Self::Custom(path) => {
+                let raw = std::fs::read_to_string(path).map_err(|e| {
+                    AppError::InvalidArg(format!("read genesis file {}: {e}", path.display()))
+                })?;
+                let genesis: Genesis = serde_json::from_str(&raw).map_err(|e| {
+                    AppError::InvalidArg(format!("parse genesis file {}: {e}", path.display()))
+                })?;
+                ChainSpec::from(genesis)
+            }
+        };
+        tracing::debug!(chain = %self, chain_id = spec.chain.id(), "Chain spec selected");
+        Ok(Arc::new(spec))
+    }
+}
+
+/// BSC 主网分叉表。
+///
+/// revm 只认以太坊分叉，这里按 BSC 实际引入对应 EIP 的硬分叉映射：
+/// Hertz -> Berlin/London，Kepler -> Shanghai，Haber -> Cancun，Pascal -> Prague。
+/// BSC 没有 Paris（合并），因此不设置 TTD。
+pub fn bsc_mainnet_spec() -> ChainSpec {
+    build_spec(
+        56,
+        BSC_MAINNET_GENESIS_HASH,
+        0x5e9da7ce,
+        31_302_048,
+        [1_705_996_800, 1_718_863_500, 1_742_436_600],
+    )
+}
+
+/// Chapel 测试网分叉表（映射规则同主网）
+pub fn bsc_chapel_spec() -> ChainSpec {
+    build_spec(
+        97,
+        BSC_CHAPEL_GENESIS_HASH,
+        0x5e9da7ce,
+        31_103_030,
+        [1_702_972_800, 1_713_330_442, 1_740_452_880],
+    )
+}
+
+/// `london_block`: Berlin/London 同块激活；`times`: Shanghai/Cancun/Prague 激活时间戳
+fn build_spec(
+    chain_id: u64,
+    genesis_hash: B256,
+    genesis_timestamp: u64,
+    london_block: u64,
+    times: [u64; 3],
+) -> ChainSpec {
+    // genesis 只携带链参数，不含 alloc；只读打开数据目录时不会重算创世状态
+    let genesis = Genesis {
+        config: ChainConfig { chain_id, ..Default::default() },
+        timestamp: genesis_timestamp,
+        gas_limit: 0x2625a00,
+        difficulty: U256::from(1),
+        ..Default::default()
+    };
+
+    let mut builder = ChainSpecBuilder::default().chain(Chain::from_id(chain_id)).genesis(genesis);
+    for fork in [
+        EthereumHardfork::Frontier,
+        EthereumHardfork::Homestead,
+        EthereumHardfork::Tangerine,
+        EthereumHardfork::SpuriousDragon,
+        EthereumHardfork::Byzantium,
+        EthereumHardfork::Constantinople,
+        EthereumHardfork::Petersburg,
+        EthereumHardfork::Istanbul,
+        EthereumHardfork::MuirGlacier,
+    ] {
+        builder = builder.with_fork(fork, ForkCondition::Block(0));
+    }
+    let [shanghai, cancun, prague] = times;
+    let mut spec = builder
+        .with_fork(EthereumHardfork::Berlin, ForkCondition::Block(london_block))
+        .with_fork(EthereumHardfork::London, ForkCondition::Block(london_block))
+        .with_fork(EthereumHardfork::Shanghai, ForkCondition::Timestamp(shanghai))
+        .with_fork(EthereumHardfork::Cancun, ForkCondition::Timestamp(cancun))
+        .with_fork(EthereumHardfork::Prague, ForkCondition::Timestamp(prague))
+        .build();
+
+    // 没有完整 alloc 无法算出真实创世哈希，直接使用已知值
+    let header = spec.genesis_header.header().clone();
+    spec.genesis_header = SealedHeader::new(header, genesis_hash);
+    spec
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_names_and_genesis_paths() {
+        assert_eq!("mainnet".parse::<BscChain>().unwrap(), BscChain::Bsc);
+        assert_eq!("97".parse::<BscChain>().unwrap(), BscChain::Chapel);
+        assert_eq!("genesis.json".parse::<BscChain>().unwrap(), BscChain::Custom("genesis.json".into()));
+        assert_eq!("./devnet/genesis".parse::<BscChain>().unwrap(), BscChain::Custom("./devnet/genesis".into()));
+    }
+
+    #[test]
+    fn rejects_unknown_chain_names() {
+        for name in ["", "bnb", "Chapel", "opbnb"] {
+            let err = name.parse::<BscChain>().unwrap_err();
+            assert!(matches!(&err, AppError::InvalidArg(msg) if msg.contains("chapel|testnet|97")), "{err}");
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use alloy_primitives::keccak256;
+use eyre::Context;
+use reth_provider::BlockBodyIndicesProvider;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::databases::BscDatabase;
+use crate::error::{AppError, Result};
+use crate::parallel::{ParallelScan, RangeScanner};
+
+/// 持久化的扫描进度
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanCheckpoint {
+    /// 扫描器标识（如 scan_ct）
+    pub scanner: String,
+    /// 影响结果的配置哈希（不含结束区块，便于向后扩展）
+    pub config_hash: String,
+    /// 最后一个完整处理的区块
+    pub last_block: u64,
+    /// 该区块最后一笔交易的 tx 编号（空块或无区块体时为 None）
+    pub last_tx_num: Option<u64>,
+    /// 写入时间（unix 秒）
+    pub updated_at: u64,
+    /// 扫描器跨分片延续的状态（恢复时原样交回扫描器）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state: Option<serde_json::Value>,
+}
+
+/// 检查点文件读写
+#[derive(Debug, Clone)]
+pub struct Checkpointer {
+    path: PathBuf,
+    scanner: String,
+    config_hash: String,
+}
+
+impl Checkpointer {
+    /// `config` 为描述扫描配置的字符串，取其 keccak 作为配置哈希
+    pub fn new(path: impl Into<PathBuf>, scanner: impl Into<String>, config: &str) -> Self {
+        Self { path: path.into(), scanner: scanner.into(), config_hash: format!("{:x}", keccak256(config.as_bytes())) }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 读取检查点；扫描器或配置不一致时报错，防止拼接出错误结果
+    pub fn load(&self) -> Result<Option<ScanCheckpoint>> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+        let raw = std::fs::read_to_string(&self.path)
+            .with_context(|| format!("read checkpoint {}", self.path.display()))?;
+        let ckpt: ScanCheckpoint = serde_json::from_str(&raw)
+            .with_context(|| format!("parse checkpoint {}", self.path.display()))?;
+        if ckpt.scanner != self.scanner {
+            return Err(AppError::InvalidArg(format!(
+                "checkpoint {} belongs to scanner {}, not {}",
+                self.path.display(),
+                ckpt.scanner,
+                self.scanner
+            )));
+        }
+        if ckpt.config_hash != self.config_hash {
+            return Err(AppError::InvalidArg(format!(
+                "checkpoint {} was written with a different scan configuration",
+                self.path.display()
+            )));
+        }
+        Ok(Some(ckpt))
+    }
+
+    /// 根据已有检查点收缩待扫描范围
+    pub fn resume_range(&self, range: Range<u64>) -> Result<Range<u64>> {
+        match self.load()? {
+            Some(ckpt) => {
+                let start = range.start.max(ckpt.last_block + 1);
+                tracing::info!(last_block = ckpt.last_block, start, end = range.end, "Resuming from checkpoint");
+                Ok(start..range.end.max(start))
+            }
+            None => Ok(range),
+        }
+    }
+
+    /// 读取检查点中保存的扫描器状态（无检查点或未保存状态时为 None）
+    pub fn load_state<T: DeserializeOwned>(&self) -> Result<Option<T>> {
+        let Some(value) = self.load()?.and_then(|ckpt| ckpt.state) else {
+            return Ok(None);
+        };
+        let state = serde_json::from_value(value)
+            .with_context(|| format!("parse checkpoint state {}", self.path.display()))?;
+        Ok(Some(state))
+    }
+
+    /// 记录 `last_block` 已完成（先写临时文件再 rename，保证原子性）
+    pub fn save(&self, db: &BscDatabase, last_block: u64) -> Result<()> {
+        self.save_with_state(db, last_block, None::<&()>)
+    }
+
+    /// 同 [`Checkpointer::save`]，并一同保存扫描器跨分片延续的状态
+    pub fn save_with_state<T: Serialize>(&self, db: &BscDatabase, last_block: u64, state: Option<&T>) -> Result<()> {
+        let state = state.map(serde_json::to_value).transpose().context("serialize checkpoint state")?;
+        let last_tx_num = db
+            .provider()?
+            .block_body_indices(last_block)?
+            .filter(|b| b.tx_count() > 0)
+            .map(|b| b.last_tx_num());
+        let updated_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let ckpt = ScanCheckpoint {
+            scanner: self.scanner.clone(),
+            config_hash: self.config_hash.clone(),
+            last_block,
+            last_tx_num,
+            updated_at,
+            state,
+        };
+        let tmp = self.path.with_extension("tmp");
+        let json = serde_json::to_vec_pretty(&ckpt).context("serialize checkpoint")?;
+        std::fs::write(&tmp, json)
+            .with_context(|| format!("write checkpoint {}", tmp.display()))?;
+        std::fs::rename(&tmp, &self.path)
+            .with_context(|| format!("rename checkpoint to {}", self.path.display()))?;
+        tracing::debug!(last_block, ?last_tx_num, "Checkpoint saved");
+        Ok(())
+    }
+}
+
+impl ParallelScan {
+    /// 带检查点的扫描：`resume` 时跳过已完成区块，每个分片交给 sink 后落盘进度
+    pub fn run_checkpointed<S, F>(
+        &self,
+        db: &BscDatabase,
+        range: Range<u64>,
+        scanner: &S,
+        checkpointer: Option<&Checkpointer>,
+        resume: bool,
+        mut sink: F,
+    ) -> Result<()>
+    where
+        S: RangeScanner,
+        F: FnMut(Range<u64>, Vec<S::Output>) -> Result<()>,
+    {
+        let Some(ckpt) = checkpointer else {
+            return self.run(db, range, scanner, sink);
+        };
+        let range = if resume { ckpt.resume_range(range)? } else { range };
+        self.run(db, range, scanner, |chunk, out| {
+            let last = chunk.end - 1;
+            sink(chunk, out)?;
+            ckpt.save(db, last)
+        })
+    }
+}
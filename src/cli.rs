@@ -1,4 +1,19 @@
-use clap::{Parser, Subcommand};
+use std::path::{Path, PathBuf};
+
+use alloy_primitives::{Address, B256};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+
+use crate::{
+    abi::AbiRegistry,
+    address_index::AddressRole,
+    chain::BscChain,
+    checkpoint::Checkpointer,
+    dex::PoolFilter,
+    events::EventRegistry,
+    output::OutputFormat,
+    databases::{BscDatabase, BscDbConfig},
+    parallel::{ParallelScan, DEFAULT_CHUNK_SIZE},
+};
 
 /// bsc_scan 命令行
 #[derive(Debug, Parser)]
@@ -8,12 +23,212 @@ pub struct Cli {
     #[arg(long, value_name = "LEVEL", global = true)]
     pub log: Option<String>,
 
+    /// 输出格式：text|json|ndjson|csv
+    #[arg(long, value_name = "FORMAT", global = true, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+
+    /// 链：bsc（默认）| chapel | genesis.json 路径
+    #[arg(long, value_name = "CHAIN", global = true, default_value = "bsc")]
+    pub chain: BscChain,
+
+    /// MDBX 子目录（相对数据目录），默认 db
+    #[arg(long, value_name = "SUBPATH", global = true, default_value = "db")]
+    pub db_subpath: PathBuf,
+
+    /// static_files 目录覆盖（默认 <db_path>/static_files）
+    #[arg(long, value_name = "PATH", global = true)]
+    pub static_files: Option<PathBuf>,
+
+    /// 监听 static_files 变化（节点仍在运行时使用）
+    #[arg(long, global = true)]
+    pub watch_static_files: bool,
+
+    /// 关闭长读事务保护（长时间全量扫描时使用）
+    #[arg(long, global = true)]
+    pub disable_long_read_safety: bool,
+
+    /// ABI 目录（文件名为合约地址或代码哈希），用于解码 calldata、返回数据与日志
+    #[arg(long, value_name = "DIR", global = true)]
+    pub abi_dir: Option<PathBuf>,
+
+    /// 额外的事件签名文件（.json/.csv，可重复），与内置事件库合并，用于标注与解码日志
+    #[arg(long, value_name = "FILE", global = true)]
+    pub event_signatures: Vec<PathBuf>,
+
     #[command(subcommand)]
     pub command: Commands,
 }
 
+impl Cli {
+    /// 由全局参数与子命令的数据目录构建统一的打开配置
+    pub fn db_config(&self, db_path: &str) -> BscDbConfig {
+        BscDbConfig {
+            db_subpath: self.db_subpath.clone(),
+            static_files: self.static_files.clone(),
+            chain: self.chain.clone(),
+            watch_static_files: self.watch_static_files,
+            disable_long_read_safety: self.disable_long_read_safety,
+            ..BscDbConfig::new(db_path)
+        }
+    }
+
+    /// 同 [`Cli::db_config`]，但允许子命令直接给出 static_files 目录：
+    /// 未给数据目录时取其父目录，且优先于全局 `--static-files`
+    pub fn db_config_with_static_dir(&self, db_path: Option<&str>, static_dir: Option<&Path>) -> BscDbConfig {
+        let datadir = match (db_path, static_dir) {
+            (Some(db_path), _) => PathBuf::from(db_path),
+            (None, Some(dir)) => dir.parent().map(Path::to_path_buf).unwrap_or_default(),
+            (None, None) => PathBuf::new(),
+        };
+        let mut config = self.db_config(&datadir.to_string_lossy());
+        if let Some(dir) = static_dir {
+            config.static_files = Some(dir.to_path_buf());
+        }
+        config
+    }
+
+    /// 加载 --abi-dir 指定的 ABI 注册表
+    pub fn abi_registry(&self) -> crate::error::Result<Option<AbiRegistry>> {
+        self.abi_dir.as_deref().map(AbiRegistry::load_dir).transpose()
+    }
+
+    /// 内置事件库 + --event-signatures 导入的签名
+    pub fn event_registry(&self) -> crate::error::Result<EventRegistry> {
+        let mut events = EventRegistry::bundled();
+        for file in &self.event_signatures {
+            events.import(file)?;
+        }
+        Ok(events)
+    }
+}
+
+/// 区块范围与并行参数（不带检查点，供结果只在内存中汇总或自行持久化进度的命令使用）
+#[derive(Debug, Clone, Args)]
+pub struct RangeArgs {
+    /// 起始区块（含）
+    #[arg(long, value_name = "BLOCK", default_value_t = 0)]
+    pub from: u64,
+    /// 结束区块（含），默认到最新块
+    #[arg(long, value_name = "BLOCK")]
+    pub to: Option<u64>,
+    /// worker 线程数，默认为 CPU 核数
+    #[arg(long, value_name = "N")]
+    pub threads: Option<usize>,
+    /// 每个分片的区块数
+    #[arg(long, value_name = "BLOCKS", default_value_t = DEFAULT_CHUNK_SIZE)]
+    pub chunk_size: u64,
+}
+
+impl RangeArgs {
+    /// 左闭右开的区块范围（裁剪到数据库可用区间）
+    pub fn range(&self, db: &BscDatabase) -> std::ops::Range<u64> {
+        let end = self.to.unwrap_or(db.latest_block).min(db.latest_block) + 1;
+        self.from.max(db.earliest_available_block)..end
+    }
+
+    pub fn engine(&self) -> ParallelScan {
+        let threads = self.threads.unwrap_or_else(|| ParallelScan::default().threads);
+        ParallelScan::new(self.chunk_size, threads)
+    }
+}
+
+/// 区块范围扫描的并行与检查点参数
+#[derive(Debug, Clone, Args)]
+pub struct ScanArgs {
+    #[command(flatten)]
+    pub blocks: RangeArgs,
+    /// 检查点文件（每个分片完成后写入进度）
+    #[arg(long, value_name = "FILE")]
+    pub checkpoint: Option<PathBuf>,
+    /// 从检查点继续（未指定 --checkpoint 时使用 <scanner>.checkpoint.json）
+    #[arg(long)]
+    pub resume: bool,
+}
+
+impl ScanArgs {
+    /// 左闭右开的区块范围（裁剪到数据库可用区间）
+    pub fn range(&self, db: &BscDatabase) -> std::ops::Range<u64> {
+        self.blocks.range(db)
+    }
+
+    /// 构建检查点；`extra` 为扫描器自身影响结果的参数
+    pub fn checkpointer(&self, db: &BscDatabase, scanner: &str, extra: &str) -> Option<Checkpointer> {
+        let path = match (&self.checkpoint, self.resume) {
+            (Some(path), _) => path.clone(),
+            (None, true) => PathBuf::from(format!("{scanner}.checkpoint.json")),
+            (None, false) => return None,
+        };
+        let config = format!("{scanner};chain={};from={};{extra}", db.chain_spec.chain.id(), self.blocks.from);
+        Some(Checkpointer::new(path, scanner, &config))
+    }
+
+    pub fn engine(&self) -> ParallelScan {
+        self.blocks.engine()
+    }
+}
+
+/// DEX 池子范围参数
+#[derive(Debug, Clone, Args)]
+pub struct PoolArgs {
+    /// 只处理这些池子（可重复；默认全部）
+    #[arg(long, value_name = "ADDRESS")]
+    pub pool: Vec<Address>,
+    /// 只接受这些工厂创建的池子（可重复；默认 PancakeSwap V2 与 V3 工厂）
+    #[arg(long, value_name = "ADDRESS")]
+    pub factory: Vec<Address>,
+    /// 不检查池子的工厂（包含其他 Uniswap 分叉的池子）
+    #[arg(long, conflicts_with = "factory")]
+    pub any_factory: bool,
+}
+
+impl PoolArgs {
+    pub fn filter(&self) -> PoolFilter {
+        let mut filter = PoolFilter { pools: self.pool.clone(), ..PoolFilter::default() };
+        if self.any_factory {
+            filter.factories.clear();
+        } else if !self.factory.is_empty() {
+            filter.factories = self.factory.clone();
+        }
+        filter
+    }
+
+    /// 供检查点配置哈希使用的描述
+    pub fn describe(&self) -> String {
+        let filter = self.filter();
+        format!("pools={:?};factories={:?}", filter.pools, filter.factories)
+    }
+}
+
+/// analyze 命令输出的记录类型
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum AnalyzeView {
+    /// 每块一行聚合统计
+    #[default]
+    Blocks,
+    /// 每笔交易一行 AnalyzedTx
+    Txs,
+    /// 每块统计后紧跟该块交易（不支持 csv）
+    All,
+}
+
+/// receipt-stats 命令输出的记录类型
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ReceiptStatsView {
+    /// 每块一行
+    Blocks,
+    /// 每个 static_files 段及 MDBX 尾部一行
+    #[default]
+    Ranges,
+}
+
 #[derive(Debug, Subcommand)]
 pub enum Commands {
+    /// 报告数据目录中各类数据的可用区块范围、裁剪与阶段检查点、static_files 段
+    Info {
+        /// 数据目录路径（包含 reth/bsc 数据库）
+        #[arg(long, value_name = "PATH")]
+        db_path: String,
+    },
     /// 根据区块号查询区块头与交易数量
     Header {
     /// 数据目录路径（包含 reth/bsc 数据库）
@@ -31,21 +246,41 @@ pub enum Commands {
         /// 区块号
         #[arg(value_name = "BLOCK_NUMBER")]
         block: u64,
+        /// 排除 BSC 系统交易（coinbase 发往系统合约、gas 价格为 0）
+        #[arg(long)]
+        exclude_system: bool,
     },
     /// 从 static_files 中测试读取 receipts 段并打印摘要
     ReceiptsTest {
-        /// static_files 目录（通常是 <db_path>/static_files）
+        /// 数据目录路径（未给出时取 --static-dir 的父目录）
+        #[arg(long, value_name = "PATH", required_unless_present = "static_dir")]
+        db_path: Option<String>,
+        /// static_files 目录（通常是 <db_path>/static_files），覆盖默认位置
         #[arg(long, value_name = "PATH")]
-        static_dir: String,
+        static_dir: Option<PathBuf>,
         /// 任意位于该段内的块号（用于定位段文件）
         #[arg(value_name = "BLOCK_NUMBER")]
         block: u64,
     },
+    /// 遍历全部 receipts static_files 段与 MDBX 尾部，统计失败率、日志数、交易类型分布与 gas 使用
+    ReceiptStats {
+        /// 数据目录路径（包含 reth/bsc 数据库）
+        #[arg(long, value_name = "PATH")]
+        db_path: String,
+        #[command(flatten)]
+        scan: ScanArgs,
+        /// 输出每块统计或按段汇总
+        #[arg(long, value_enum, default_value_t = ReceiptStatsView::Ranges)]
+        view: ReceiptStatsView,
+    },
     /// 从 static_files 中读取某区块的交易并打印摘要
     TransactionsTest {
-        /// static_files 目录（例如 /ethereum_data/bsc/static_files）
+        /// 数据目录路径（未给出时取 --static-dir 的父目录）
+        #[arg(long, value_name = "PATH", required_unless_present = "static_dir")]
+        db_path: Option<String>,
+        /// static_files 目录（通常是 <db_path>/static_files），覆盖默认位置
         #[arg(long, value_name = "PATH")]
-        static_dir: String,
+        static_dir: Option<PathBuf>,
         /// 区块号
         #[arg(value_name = "BLOCK_NUMBER")]
         block: u64,
@@ -62,11 +297,184 @@ pub enum Commands {
         #[arg(long, value_name = "TX_HASH")] 
         tx: Option<String>,
     },
-    /// 扫描所有合约创建交易（遍历 Transactions 表，to == None）
+    /// 并行扫描区块范围内所有合约创建交易（to == None）
     ScanCt {
         /// 数据目录路径（包含 db 子目录）
         #[arg(long, value_name = "PATH")]
         db_path: String,
+        #[command(flatten)]
+        scan: ScanArgs,
+    },
+    /// 分析区块范围：逐笔交易摘要与每块聚合统计（类型、gas 利用率、gas 价格分布、合约创建、输入大小）
+    Analyze {
+        /// 数据目录路径（包含 reth/bsc 数据库）
+        #[arg(long, value_name = "PATH")]
+        db_path: String,
+        #[command(flatten)]
+        scan: ScanArgs,
+        /// 输出内容：blocks | txs | all
+        #[arg(long, value_enum, default_value_t = AnalyzeView::Blocks)]
+        view: AnalyzeView,
+        /// 排除 BSC 系统交易（不计入交易行与费用统计，仅计数）
+        #[arg(long)]
+        exclude_system: bool,
+    },
+    /// 按哈希查询交易：完整字段、发送者、回执、日志与创建的合约地址
+    Tx {
+        /// 数据目录路径（包含 reth/bsc 数据库）
+        #[arg(long, value_name = "PATH")]
+        db_path: String,
+        /// 交易哈希（0x 开头）
+        #[arg(value_name = "TX_HASH")]
+        hash: B256,
+    },
+    /// 重放交易并输出调用树（配合 --abi-dir 解码调用参数与返回值）
+    Trace {
+        /// 数据目录路径（包含 reth/bsc 数据库）
+        #[arg(long, value_name = "PATH")]
+        db_path: String,
+        /// 交易哈希（0x 开头）
+        #[arg(value_name = "TX_HASH")]
+        hash: B256,
+    },
+    /// 按地址与 topic 位置过滤日志（等价于 eth_getLogs，利用区块 logs bloom 跳过不匹配的区块）
+    Logs {
+        /// 数据目录路径（包含 reth/bsc 数据库）
+        #[arg(long, value_name = "PATH")]
+        db_path: String,
+        #[command(flatten)]
+        scan: ScanArgs,
+        /// 日志地址（可重复，OR）
+        #[arg(long, value_name = "ADDRESS")]
+        address: Vec<Address>,
+        /// topic0（可重复，OR）
+        #[arg(long, value_name = "TOPIC")]
+        topic0: Vec<B256>,
+        /// topic1（可重复，OR）
+        #[arg(long, value_name = "TOPIC")]
+        topic1: Vec<B256>,
+        /// topic2（可重复，OR）
+        #[arg(long, value_name = "TOPIC")]
+        topic2: Vec<B256>,
+        /// topic3（可重复，OR）
+        #[arg(long, value_name = "TOPIC")]
+        topic3: Vec<B256>,
+    },
+    /// 从回执中提取 BEP-20 Transfer 事件（排除 tokenId indexed 的 ERC-721 形状）
+    TokenTransfers {
+        /// 数据目录路径（包含 reth/bsc 数据库）
+        #[arg(long, value_name = "PATH")]
+        db_path: String,
+        #[command(flatten)]
+        scan: ScanArgs,
+        /// 只提取这些代币合约（可重复；默认全部）
+        #[arg(long, value_name = "ADDRESS")]
+        token: Vec<Address>,
+    },
+    /// 从回执中提取 ERC-721 Transfer 与 ERC-1155 TransferSingle/TransferBatch（批量转账按 id 展开）
+    NftTransfers {
+        /// 数据目录路径（包含 reth/bsc 数据库）
+        #[arg(long, value_name = "PATH")]
+        db_path: String,
+        #[command(flatten)]
+        scan: ScanArgs,
+        /// 只提取这些 NFT 合约（可重复；默认全部）
+        #[arg(long, value_name = "ADDRESS")]
+        token: Vec<Address>,
+    },
+    /// 提取 PancakeSwap V2 Swap/Sync 与 V3 Swap，按历史状态解析池子代币与费率，输出归一化兑换表
+    Swaps {
+        /// 数据目录路径（包含 reth/bsc 数据库）
+        #[arg(long, value_name = "PATH")]
+        db_path: String,
+        #[command(flatten)]
+        scan: ScanArgs,
+        #[command(flatten)]
+        pools: PoolArgs,
+    },
+    /// 由 V2 Sync 与 V3 Swap 重建池子逐块储备/sqrtPrice，输出逐块价格序列（离线价格预言机）。
+    ///
+    /// 输出是稀疏的：池子只在有 Sync/Swap 的区块出一行，其余区块沿用该池子的上一行。
+    /// 池子状态随 --checkpoint 保存，--resume 后继续延续。
+    Prices {
+        /// 数据目录路径（包含 reth/bsc 数据库）
+        #[arg(long, value_name = "PATH")]
+        db_path: String,
+        #[command(flatten)]
+        scan: ScanArgs,
+        #[command(flatten)]
+        pools: PoolArgs,
+    },
+    /// 构建或增量更新本地地址索引（address -> tx 编号，区分 from/to/created/log）
+    AddressIndex {
+        /// 数据目录路径（包含 reth/bsc 数据库）
+        #[arg(long, value_name = "PATH")]
+        db_path: String,
+        /// 索引目录
+        #[arg(long, value_name = "DIR")]
+        index_dir: PathBuf,
+        /// --from 只用于首次构建；已有索引时从上次结束处继续。进度由索引自身保存，不使用检查点
+        #[command(flatten)]
+        blocks: RangeArgs,
+    },
+    /// 从本地地址索引分页查询地址相关交易（按 tx 编号升序）
+    AddressTxs {
+        /// 数据目录路径（包含 reth/bsc 数据库）
+        #[arg(long, value_name = "PATH")]
+        db_path: String,
+        /// 索引目录（由 address-index 构建）
+        #[arg(long, value_name = "DIR")]
+        index_dir: PathBuf,
+        /// 地址（0x 开头）
+        #[arg(value_name = "ADDRESS")]
+        address: Address,
+        /// 只返回这些角色（可重复；默认全部）
+        #[arg(long, value_enum)]
+        role: Vec<AddressRole>,
+        /// 游标：上一页最后一条的 tx 编号
+        #[arg(long, value_name = "TX_NUM")]
+        after: Option<u64>,
+        /// 每页条数
+        #[arg(long, default_value_t = 100)]
+        limit: usize,
+    },
+    /// 账户变更时间线：列出余额、nonce 或代码发生变化的区块及前后值
+    AccountHistory {
+        /// 数据目录路径（包含 reth/bsc 数据库）
+        #[arg(long, value_name = "PATH")]
+        db_path: String,
+        /// 账户地址（0x 开头）
+        #[arg(value_name = "ADDRESS")]
+        address: Address,
+        /// 起始区块（含）
+        #[arg(long, value_name = "BLOCK", default_value_t = 0)]
+        from: u64,
+        /// 结束区块（含），默认到最新块
+        #[arg(long, value_name = "BLOCK")]
+        to: Option<u64>,
+    },
+    /// 统计区块范围内最常被调用的函数（按 selector，可按合约分组，签名由本地签名库解析）
+    Selectors {
+        /// 数据目录路径（包含 reth/bsc 数据库）
+        #[arg(long, value_name = "PATH")]
+        db_path: String,
+        #[command(flatten)]
+        blocks: RangeArgs,
+        /// 按被调用合约分组
+        #[arg(long)]
+        by_contract: bool,
+        /// 只统计这些合约（可重复）
+        #[arg(long, value_name = "ADDRESS")]
+        contract: Vec<Address>,
+        /// 额外导入的签名文件（.json / .csv，可重复）
+        #[arg(long, value_name = "FILE")]
+        signatures: Vec<PathBuf>,
+        /// 每个合约（或整个范围）输出的条数
+        #[arg(long, default_value_t = 20)]
+        top: usize,
+        /// 排除 BSC 系统交易
+        #[arg(long)]
+        exclude_system: bool,
     },
     /// 扫描指定区块内所有交易是否为合约创建
     ScanCtBlock {
@@ -76,6 +484,9 @@ pub enum Commands {
         /// 区块号
         #[arg(value_name = "BLOCK_NUMBER")]
         block: u64,
+        /// 排除 BSC 系统交易
+        #[arg(long)]
+        exclude_system: bool,
     },
     /// 执行单区块 EVM 追踪，输出 CREATE/CREATE2 合约创建 (evm_ct_test)
     #[command(name = "evm_ct_test")]
@@ -1,36 +1,29 @@
-use std::path::Path;
-
-use eyre::{Context as _, Result};
 use alloy_primitives::{Address, B256, U256};
-use reth_chainspec::ChainSpecBuilder;
-use reth_node_ethereum::node::EthereumNode;
-use reth_provider::providers::ReadOnlyConfig;
 use reth_provider::{
     BlockBodyIndicesProvider, BlockNumReader, HeaderProvider, ReceiptProvider, StateProviderFactory,
     TransactionsProvider,
 };
 use alloy_consensus::transaction::Transaction; // bring to()/gas/nonce/value APIs
 
+use crate::databases::BscDatabase;
+use crate::error::Result;
+
 /// 演示 _provider 的常见查询：区块头、交易、回执、体索引、状态等
-pub fn demo_common(datadir: impl AsRef<Path>, block_number: u64, tx_hash: Option<B256>) -> Result<()> {
-    let datadir = datadir.as_ref();
-    let spec = ChainSpecBuilder::mainnet().build();
-    let factory = EthereumNode::provider_factory_builder()
-        .open_read_only(spec.into(), ReadOnlyConfig::from_datadir(datadir))
-        .context("open_read_only provider factory")?;
-    let provider = factory.provider().context("get read-only provider")?;
+pub fn demo_common(db: &BscDatabase, block_number: u64, tx_hash: Option<B256>) -> Result<()> {
+    let factory = &db.provider_factory;
+    let provider = factory.provider()?;
     // 1) 基本高度/区块头
-    let latest = provider.best_block_number().context("best_block_number")?;
+    let latest = provider.best_block_number()?;
     tracing::info!(latest, "latest block number");
 
-    if let Some(header) = provider.header_by_number(block_number).context("header_by_number")? {
+    if let Some(header) = provider.header_by_number(block_number)? {
         tracing::info!(block = header.number, timestamp = %header.timestamp, gas_used = %header.gas_used, "header fetched");
     } else {
         tracing::warn!(block_number, "header not found");
     }
 
     // 2) 区块体索引（定位交易范围）
-    if let Some(indices) = provider.block_body_indices(block_number).context("block_body_indices")? {
+    if let Some(indices) = provider.block_body_indices(block_number)? {
         tracing::info!(first_tx = indices.first_tx_num(), tx_count = indices.tx_count(), "body indices");
     } else {
         tracing::warn!(block_number, "body indices not found");
@@ -74,7 +67,7 @@ pub fn demo_common(datadir: impl AsRef<Path>, block_number: u64, tx_hash: Option
     }
 
     // 6) 状态访问（通过 factory.latest() 获取 StateProvider）
-    let state = factory.latest().context("factory.latest() state provider")?;
+    let state = factory.latest()?;
     let addr: Address = Address::ZERO;
     match state.basic_account(&addr) {
         Ok(Some(acc)) => tracing::info!(balance = %acc.balance, nonce = acc.nonce, "basic account for ZERO"),
@@ -1,28 +1,94 @@
 use alloy_primitives::{Address, U256, B256};
-use eyre::{Context, Result};
-use std::sync::Arc;
-use std::path::Path;
+use eyre::Context;
+use std::sync::{Arc, OnceLock};
+use std::path::{Path, PathBuf};
 
 use reth_node_ethereum::node::EthereumNode;
-use reth_chainspec::ChainSpecBuilder;
+use reth_chainspec::ChainSpec;
 use reth_provider::{
-    providers::ReadOnlyConfig,
+    providers::{ReadOnlyConfig, StaticFileProvider},
     HeaderProvider, ReceiptProvider, StateProvider, TransactionsProvider,
-    ProviderFactory, BlockNumReader, BlockBodyIndicesProvider,
+    ProviderFactory, BlockNumReader, BlockBodyIndicesProvider, DatabaseProviderRO,
+    StaticFileProviderFactory, StateProviderBox,
 };
 use reth_node_api::NodeTypesWithDBAdapter;
 use reth_db::DatabaseEnv;
 use reth_primitives::{Header, TransactionSigned};
-use reth_ethereum_primitives::Receipt as EthReceipt;
+use reth_ethereum_primitives::{EthPrimitives, Receipt as EthReceipt};
+use reth_storage_errors::provider::ProviderError;
 
+use crate::chain::BscChain;
+use crate::error::{AppError, Result};
+use crate::info::{DataAvailability, DataKind};
 
+/// 只读打开的节点类型（EthereumNode + MDBX）
+pub type BscNodeTypes = NodeTypesWithDBAdapter<EthereumNode, Arc<DatabaseEnv>>;
+/// 本库统一使用的 ProviderFactory
+pub type BscProviderFactory = ProviderFactory<BscNodeTypes>;
+/// `provider_factory.provider()` 返回的只读 provider
+pub type BscProvider = DatabaseProviderRO<Arc<DatabaseEnv>, BscNodeTypes>;
+
+/// 打开数据目录的配置；所有子命令与库函数都通过它打开节点数据
+#[derive(Debug, Clone)]
+pub struct BscDbConfig {
+    /// 数据目录（包含 db 与 static_files）
+    pub datadir: PathBuf,
+    /// MDBX 所在子目录（相对 datadir），默认 `db`
+    pub db_subpath: PathBuf,
+    /// static_files 目录覆盖；None 时使用 `<datadir>/static_files`
+    pub static_files: Option<PathBuf>,
+    /// 链选择
+    pub chain: BscChain,
+    /// 监听 static_files 目录变化（节点仍在写入时打开）
+    pub watch_static_files: bool,
+    /// 关闭长读事务保护（全量扫描时避免只读事务被超时终止）
+    pub disable_long_read_safety: bool,
+}
+
+impl BscDbConfig {
+    /// 以默认布局（db + static_files，BSC 主网）打开 datadir
+    pub fn new(datadir: impl Into<PathBuf>) -> Self {
+        Self {
+            datadir: datadir.into(),
+            db_subpath: PathBuf::from("db"),
+            static_files: None,
+            chain: BscChain::Bsc,
+            watch_static_files: false,
+            disable_long_read_safety: false,
+        }
+    }
+
+    /// MDBX 目录
+    pub fn db_dir(&self) -> PathBuf {
+        self.datadir.join(&self.db_subpath)
+    }
+
+    /// static_files 目录
+    pub fn static_files_dir(&self) -> PathBuf {
+        self.static_files.clone().unwrap_or_else(|| self.datadir.join("static_files"))
+    }
+
+    fn read_only_config(&self) -> ReadOnlyConfig {
+        let mut config = ReadOnlyConfig::from_dirs(self.db_dir(), self.static_files_dir());
+        if !self.watch_static_files {
+            config = config.no_watch();
+        }
+        if self.disable_long_read_safety {
+            config = config.disable_long_read_transaction_safety();
+        }
+        config
+    }
+}
 
 /// Comprehensive BSC database testing utility
 pub struct BscDatabase {
     // Use the actual type returned by the factory builder
-    pub provider_factory: ProviderFactory<NodeTypesWithDBAdapter<EthereumNode, Arc<DatabaseEnv>>>,
+    pub provider_factory: BscProviderFactory,
+    pub chain_spec: Arc<ChainSpec>,
+    pub config: BscDbConfig,
     pub latest_block: u64,
     pub earliest_available_block: u64,
+    availability_cache: OnceLock<DataAvailability>,
 }
 
 /// 根据区块号查询得到的区块数据
@@ -32,16 +98,40 @@ pub struct BlockData {
 }
 
 impl BscDatabase {
-    /// Create a new BSC database test instance
+    /// Create a new BSC database test instance (BSC mainnet chain spec)
     pub fn new<P: AsRef<Path>>(db_path: P) -> Result<Self> {
-        tracing::info!(path = %db_path.as_ref().display(), "Opening BSC database");
-        
-        // Create chain spec (use mainnet for now, can be customized for BSC)
-        let spec = ChainSpecBuilder::mainnet().build();
-        
+        Self::open(BscDbConfig::new(db_path.as_ref()))
+    }
+
+    /// 按指定链（bsc/chapel/自定义 genesis）打开数据目录
+    pub fn with_chain<P: AsRef<Path>>(db_path: P, chain: &BscChain) -> Result<Self> {
+        Self::open(BscDbConfig { chain: chain.clone(), ..BscDbConfig::new(db_path.as_ref()) })
+    }
+
+    /// 统一入口：按配置只读打开 MDBX 与 static_files
+    pub fn open(config: BscDbConfig) -> Result<Self> {
+        tracing::info!(
+            db = %config.db_dir().display(),
+            static_files = %config.static_files_dir().display(),
+            chain = %config.chain,
+            "Opening BSC database",
+        );
+        
+        let chain_spec = config.chain.chain_spec()?;
+
+        // 数据目录不存在时直接报 Database 错误并指明路径
+        let db_dir = config.db_dir();
+        if !db_dir.is_dir() {
+            return Err(AppError::Database(ProviderError::FsPathError(format!(
+                "database directory {} does not exist",
+                db_dir.display()
+            ))));
+        }
+
         // Create provider factory using the EthereumNode
         let provider_factory = EthereumNode::provider_factory_builder()
-            .open_read_only(spec.into(), ReadOnlyConfig::from_datadir(db_path))?;
+            .open_read_only(chain_spec.clone(), config.read_only_config())
+            .map_err(AppError::from)?;
         
     tracing::info!("Database opened successfully");
         
@@ -51,23 +141,39 @@ impl BscDatabase {
         
         Ok(Self {
             provider_factory,
+            chain_spec,
+            config,
             latest_block,
             earliest_available_block,
+            availability_cache: OnceLock::new(),
         })
     }
+
+    /// 只读 provider（MDBX + static_files 统一视图）
+    pub fn provider(&self) -> Result<BscProvider> {
+        Ok(self.provider_factory.provider()?)
+    }
+
+    /// 原始 MDBX 环境，用于直接游标遍历表
+    pub fn mdbx(&self) -> &Arc<DatabaseEnv> {
+        self.provider_factory.db_ref()
+    }
+
+    /// static_files 提供者（与 provider_factory 共享同一实例）
+    pub fn static_file_provider(&self) -> StaticFileProvider<EthPrimitives> {
+        self.provider_factory.static_file_provider()
+    }
     
     /// Detect node type and available block range
     fn detect_node_characteristics(
-        provider_factory: &ProviderFactory<NodeTypesWithDBAdapter<EthereumNode, Arc<DatabaseEnv>>>
+        provider_factory: &BscProviderFactory
     ) -> Result<(u64, u64)> {
         tracing::info!("Detecting node type and available data range...");
         
         let provider = provider_factory.provider()?;
         
         // Get latest block
-        let latest_block = provider
-            .best_block_number()
-            .context("Failed to get latest block number")?;
+        let latest_block = provider.best_block_number()?;
         
     tracing::info!(latest_block, "Latest block");
         
@@ -146,28 +252,52 @@ impl BscDatabase {
         Ok(())
     }
     
-    /// 根据交易哈希读取 receipt（自动从 MDBX 或 static_files）
-    pub fn receipt_by_hash(&self, tx_hash: B256) -> Result<Option<EthReceipt>> {
-        let provider = self.provider_factory.provider()?;
-        // 先将 TxHash 映射为内部 TxNumber
-        match provider.transaction_id(tx_hash)? {
-            Some(tx_num) => Ok(provider.receipt(tx_num)?),
-            None => Ok(None),
+    /// 缓存的数据可用性报告（首次使用时生成）
+    pub fn cached_availability(&self) -> Result<&DataAvailability> {
+        if let Some(report) = self.availability_cache.get() {
+            return Ok(report);
         }
+        let report = self.availability()?;
+        Ok(self.availability_cache.get_or_init(|| report))
     }
 
-    /// 根据区块号返回该区块的数据（头 + 交易数量）。不存在则返回 None。
-    pub fn query_headers_with_blocknumber(&self, block_number: u64) -> Result<Option<BlockData>> {
-        let provider = self.provider_factory.provider()?;
+    /// 检查某类数据在 `block_number` 是否可用：
+    /// 超过最新块返回 OutOfRange，落在裁剪区间内返回 Pruned
+    pub fn ensure_available(&self, kind: DataKind, block_number: u64) -> Result<()> {
+        if block_number > self.latest_block {
+            return Err(AppError::OutOfRange { block: block_number, latest: self.latest_block });
+        }
+        let report = self.cached_availability()?;
+        match report.range(kind).and_then(|r| r.first) {
+            Some(first) if block_number >= first => Ok(()),
+            _ => {
+                tracing::debug!(block_number, %kind, "Data pruned");
+                Err(AppError::Pruned { kind, block: block_number })
+            }
+        }
+    }
 
-        if block_number < self.earliest_available_block {
-            tracing::debug!(block_number, earliest = self.earliest_available_block, "Header not available before earliest block");
+    /// 根据交易哈希读取 receipt（自动从 MDBX 或 static_files）。
+    /// 哈希不存在返回 None；receipt 已被裁剪返回 Pruned。
+    pub fn receipt_by_hash(&self, tx_hash: B256) -> Result<Option<EthReceipt>> {
+        let provider = self.provider()?;
+        // 先将 TxHash 映射为内部 TxNumber
+        let Some(tx_num) = provider.transaction_id(tx_hash)? else {
             return Ok(None);
+        };
+        if let Some(block) = provider.transaction_block(tx_num)? {
+            self.ensure_available(DataKind::Receipts, block)?;
         }
+        Ok(provider.receipt(tx_num)?)
+    }
+
+    /// 根据区块号返回该区块的数据（头 + 交易数量）。
+    /// 区块头缺失返回 None；超过最新块返回 OutOfRange；已被裁剪返回 Pruned。
+    pub fn query_headers_with_blocknumber(&self, block_number: u64) -> Result<Option<BlockData>> {
+        self.ensure_available(DataKind::Headers, block_number)?;
 
-        let header_opt = provider
-            .header_by_number(block_number)
-            .context("Failed to get header by number")?;
+        let provider = self.provider()?;
+        let header_opt = provider.header_by_number(block_number)?;
 
         if let Some(header) = header_opt {
             // 获取交易数量（如果存在区块体）
@@ -192,39 +322,42 @@ impl BscDatabase {
         }
     }
     
-    /// 查询指定区块号下的所有交易，若无区块体或无交易则返回空 Vec
+    /// 查询指定区块号下的所有交易。
+    /// 空 Vec 仅表示区块确实没有交易；区块体缺失返回 NotFound，超过最新块返回 OutOfRange，
+    /// 交易已被裁剪返回 Pruned。
     pub fn query_block_order_transactions(&self, block_number: u64) -> Result<Vec<TransactionSigned>> {
-        let provider = self.provider_factory.provider()?;
-
-        if block_number < self.earliest_available_block {
-            tracing::debug!(block_number, earliest = self.earliest_available_block, "Transactions not available before earliest block");
-            return Ok(Vec::new());
-        }
+        self.ensure_available(DataKind::Bodies, block_number)?;
+        let provider = self.provider()?;
 
         // 先检查是否存在区块体，可快速判定是否有交易
-        match provider.block_body_indices(block_number) {
+        let tx_count = match provider.block_body_indices(block_number) {
             Ok(Some(body)) => {
                 let tx_count = body.tx_count();
                 tracing::info!(block_number, tx_count, "Block body found, loading transactions");
+                tx_count
             }
             Ok(None) => {
                 tracing::debug!(block_number, "Block body not found");
-                return Ok(Vec::new());
+                return Err(AppError::NotFound(format!("block body {block_number}")));
             }
             Err(e) => {
                 tracing::warn!(block_number, error = %e, "Block body read error");
                 return Err(e.into());
             }
+        };
+        if tx_count == 0 {
+            return Ok(Vec::new());
         }
 
         match provider.transactions_by_block(block_number.into()) {
-            Ok(Some(txs)) => {
+            Ok(Some(txs)) if txs.len() as u64 == tx_count => {
                 tracing::info!(block_number, count = txs.len(), "Fetched transactions for block");
                 Ok(txs)
             }
-            Ok(None) => {
-                tracing::debug!(block_number, "No transactions found for block");
-                Ok(Vec::new())
+            Ok(_) => {
+                // 区块体索引声明有交易但读不到：交易数据已被删除
+                tracing::debug!(block_number, tx_count, "Transactions missing for non-empty block");
+                Err(AppError::Pruned { kind: DataKind::Bodies, block: block_number })
             }
             Err(e) => {
                 tracing::warn!(block_number, error = %e, "Failed to load transactions for block");
@@ -232,14 +365,49 @@ impl BscDatabase {
             }
         }
     }
+
+    /// 查询指定区块所有交易的发送者（来自 TransactionSenders）
+    pub fn query_block_senders(&self, block_number: u64) -> Result<Vec<Address>> {
+        self.ensure_available(DataKind::Senders, block_number)?;
+        let provider = self.provider()?;
+        let body = provider
+            .block_body_indices(block_number)?
+            .ok_or_else(|| AppError::NotFound(format!("block body {block_number}")))?;
+        let senders = provider.senders_by_tx_range(body.tx_num_range())?;
+        if senders.len() as u64 != body.tx_count() {
+            return Err(AppError::Pruned { kind: DataKind::Senders, block: block_number });
+        }
+        Ok(senders)
+    }
+
+    /// 查询指定区块的全部 receipt；已被裁剪返回 Pruned
+    pub fn query_block_receipts(&self, block_number: u64) -> Result<Vec<EthReceipt>> {
+        self.ensure_available(DataKind::Receipts, block_number)?;
+        let provider = self.provider()?;
+        match provider.receipts_by_block(block_number.into())? {
+            Some(receipts) => Ok(receipts),
+            None => Err(AppError::Pruned { kind: DataKind::Receipts, block: block_number }),
+        }
+    }
+
+    /// 获取 `block_number` 执行完成后的历史状态；历史索引已被裁剪返回 Pruned
+    pub fn state_at_block(&self, block_number: u64) -> Result<StateProviderBox> {
+        if block_number > self.latest_block {
+            return Err(AppError::OutOfRange { block: block_number, latest: self.latest_block });
+        }
+        if block_number < self.latest_block {
+            self.ensure_available(DataKind::AccountHistory, block_number)?;
+            self.ensure_available(DataKind::StorageHistory, block_number)?;
+        }
+        Ok(self.provider_factory.history_by_block_number(block_number)?)
+    }
     
     /// Test state provider functionality with system contracts
     pub fn test_state(&self) -> Result<()> {
     tracing::info!("Testing State Provider...");
         
         // Always use latest state to avoid pruning issues
-        let state_provider = self.provider_factory.latest()
-            .context("Failed to get latest state provider")?;
+        let state_provider = self.provider_factory.latest()?;
         
         // Test some known addresses
         let test_contracts = vec![
@@ -277,7 +445,9 @@ impl BscDatabase {
         }
         
         // Test storage for a known contract
-        let test_address: Address = "0x0000000000000000000000000000000000001000".parse()?;
+        let test_address: Address = "0x0000000000000000000000000000000000001000"
+            .parse()
+            .context("Failed to parse contract address")?;
     tracing::info!("Testing storage access for system contract");
         
         // Test common storage slots
@@ -429,30 +599,3 @@ pub fn run_bsc_database_tests<P: AsRef<Path>>(db_path: P) -> Result<()> {
     let db = BscDatabase::new(db_path)?;
     db.run_all_tests()
 }
-
-
-pub fn test_receipts(db_path: impl AsRef<Path>, block_number: u64) -> eyre::Result<()> {
-    // 打开数据库（只读）
-    let db = BscDatabase::new(db_path)?;
-    let provider = db.provider_factory.provider()?;
-
-    match provider.receipts_by_block(block_number.into())? {
-        Some(receipts) => {
-            for (i, receipt) in receipts.iter().enumerate() {
-                println!(
-                    "idx={} tx_type={:?} success={} cumulative_gas_used={} logs={}",
-                    i,
-                    receipt.tx_type,
-                    receipt.success,
-                    receipt.cumulative_gas_used,
-                    receipt.logs.len(),
-                );
-            }
-        }
-        None => {
-            println!("No receipts found for block {}", block_number);
-        }
-    }
-
-    Ok(())
-}
\ No newline at end of file
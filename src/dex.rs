@@ -0,0 +1,570 @@
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::ops::Range;
+
+use alloy_evm::Evm;
+use alloy_primitives::{address, b256, Address, Bytes, B256, I256, U256};
+use reth_ethereum::evm::{primitives::ConfigureEvm, revm::database::StateProviderDatabase, EthEvmConfig};
+use reth_primitives::Header;
+use reth_provider::{HeaderProvider, StateProvider, StateProviderBox};
+use serde::{Deserialize, Serialize};
+
+use crate::checkpoint::Checkpointer;
+use crate::databases::{BscDatabase, BscProvider};
+use crate::error::{AppError, Result};
+use crate::logs::{scan_logs, LogFilter, LogRow};
+use crate::output::Record;
+use crate::parallel::ParallelScan;
+use crate::tokens::topic_address;
+
+/// PancakeSwap V2 Factory
+pub const PANCAKE_V2_FACTORY: Address = address!("cA143Ce32Fe78f1f7019d7d551a6402fC5350c73");
+/// PancakeSwap V3 Factory
+pub const PANCAKE_V3_FACTORY: Address = address!("0BFbCF9fa4f9C56B0F40a671Ad40E0805A091865");
+
+/// keccak256("Swap(address,uint256,uint256,uint256,uint256,address)")
+pub const V2_SWAP_TOPIC: B256 = b256!("d78ad95fa46c994b6551d0da85fc275fe613ce37657fb8d5e3d130840159d822");
+/// keccak256("Sync(uint112,uint112)")
+pub const V2_SYNC_TOPIC: B256 = b256!("1c411e9a96e071241c2f21f7726b17ae89e3cab4c78be50e062b03a9fffbbad1");
+/// keccak256("Swap(address,address,int256,int256,uint160,uint128,int24,uint128,uint128)")（PancakeSwap V3，含协议费字段）
+pub const V3_SWAP_TOPIC: B256 = b256!("19b47279256b2a23a1665c810c8d55a1758940ee09377d4f8d26497a3577dc83");
+
+/// V2 池子固定费率 0.25%，与 V3 一样以百万分之一为单位
+pub const V2_FEE: u32 = 2500;
+
+/// V2 Pair 存储槽：factory / token0 / token1（前 5 个槽为 ERC-20 与 permit 状态）
+const V2_FACTORY_SLOT: u64 = 5;
+const V2_TOKEN0_SLOT: u64 = 6;
+const V2_TOKEN1_SLOT: u64 = 7;
+
+/// V3 Pool 的 immutable 访问器 selector：factory() / token0() / token1() / fee()
+const V3_FACTORY: [u8; 4] = [0xc4, 0x5a, 0x01, 0x55];
+const V3_TOKEN0: [u8; 4] = [0x0d, 0xfe, 0x16, 0x81];
+const V3_TOKEN1: [u8; 4] = [0xd2, 0x12, 0x20, 0xa7];
+const V3_FEE: [u8; 4] = [0xdd, 0xca, 0x3f, 0x43];
+/// BEP-20 decimals()
+const DECIMALS: [u8; 4] = [0x31, 0x3c, 0xe5, 0x67];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PoolKind {
+    V2,
+    V3,
+}
+
+/// 池子元数据
+#[derive(Debug, Clone, Serialize)]
+pub struct PoolInfo {
+    pub pool: Address,
+    pub kind: PoolKind,
+    pub factory: Address,
+    pub token0: Address,
+    pub token1: Address,
+    /// 费率（百万分之一）
+    pub fee: u32,
+}
+
+/// 从日志解码出的池子事件；金额均为池子视角（V3 正数表示池子收到）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PoolEvent {
+    V2Swap { sender: Address, to: Address, amount0_in: U256, amount1_in: U256, amount0_out: U256, amount1_out: U256 },
+    V2Sync { reserve0: U256, reserve1: U256 },
+    V3Swap { sender: Address, recipient: Address, amount0: I256, amount1: I256, sqrt_price_x96: U256, liquidity: u128, tick: i32 },
+}
+
+impl PoolEvent {
+    pub fn kind(&self) -> PoolKind {
+        match self {
+            PoolEvent::V2Swap { .. } | PoolEvent::V2Sync { .. } => PoolKind::V2,
+            PoolEvent::V3Swap { .. } => PoolKind::V3,
+        }
+    }
+
+    /// 按 topic0 与形状解码；非池子事件或数据长度不符时返回 None
+    pub fn from_log(topics: &[B256], data: &[u8]) -> Option<Self> {
+        let word = |i: usize| data.get(i * 32..(i + 1) * 32).map(U256::from_be_slice);
+        let topic0 = *topics.first()?;
+        if topic0 == V2_SWAP_TOPIC && topics.len() == 3 && data.len() == 128 {
+            return Some(PoolEvent::V2Swap {
+                sender: topic_address(&topics[1]),
+                to: topic_address(&topics[2]),
+                amount0_in: word(0)?,
+                amount1_in: word(1)?,
+                amount0_out: word(2)?,
+                amount1_out: word(3)?,
+            });
+        }
+        if topic0 == V2_SYNC_TOPIC && topics.len() == 1 && data.len() == 64 {
+            return Some(PoolEvent::V2Sync { reserve0: word(0)?, reserve1: word(1)? });
+        }
+        if topic0 == V3_SWAP_TOPIC && topics.len() == 3 && data.len() == 224 {
+            return Some(PoolEvent::V3Swap {
+                sender: topic_address(&topics[1]),
+                recipient: topic_address(&topics[2]),
+                amount0: I256::from_raw(word(0)?),
+                amount1: I256::from_raw(word(1)?),
+                sqrt_price_x96: word(2)?,
+                liquidity: u128::try_from(word(3)?).ok()?,
+                tick: i32::try_from(I256::from_raw(word(4)?)).ok()?,
+            });
+        }
+        None
+    }
+}
+
+/// 通过历史 StateProvider 解析池子元数据并缓存。
+///
+/// V2 的 factory/token0/token1 直接读 Pair 存储槽；V3 的这些字段是 immutable（在字节码中而非存储中），
+/// 因此在历史状态上执行只读调用。历史状态被裁剪时退回最新状态（这些字段部署后不再变化）并告警；
+/// 退回最新状态得到的否定结果（如池子在最新状态已自毁）不缓存。
+pub struct PoolResolver<'a> {
+    db: &'a BscDatabase,
+    evm_config: EthEvmConfig,
+    /// 只接受这些工厂创建的池子（为空表示不检查）
+    factories: Vec<Address>,
+    cache: HashMap<Address, Option<PoolInfo>>,
+    decimals: HashMap<Address, Option<u8>>,
+    /// 是否已告警过退回最新状态（只告警一次）
+    fallback_warned: Cell<bool>,
+    /// 只读调用复用的 provider 与执行环境区块头，由 [`PoolResolver::refresh`] 释放
+    provider: Option<BscProvider>,
+    header: Option<Header>,
+}
+
+impl<'a> PoolResolver<'a> {
+    pub fn new(db: &'a BscDatabase, factories: Vec<Address>) -> Self {
+        Self {
+            db,
+            evm_config: EthEvmConfig::new(db.chain_spec.clone()),
+            factories,
+            cache: HashMap::new(),
+            decimals: HashMap::new(),
+            fallback_warned: Cell::new(false),
+            provider: None,
+            header: None,
+        }
+    }
+
+    /// 释放复用的 provider（每个分片或区块开始时调用，避免长时间持有只读事务）
+    pub fn refresh(&mut self) {
+        self.provider = None;
+        self.header = None;
+    }
+
+    /// 解析 `pool` 在 `block` 时的元数据；不是池子或工厂不在白名单时返回 None
+    pub fn resolve(&mut self, pool: Address, kind: PoolKind, block: u64) -> Result<Option<PoolInfo>> {
+        if let Some(info) = self.cache.get(&pool) {
+            return Ok(info.clone());
+        }
+        let (state, state_block) = self.state(block)?;
+        let info = match kind {
+            PoolKind::V2 => self.resolve_v2(state.as_ref(), pool)?,
+            PoolKind::V3 => self.resolve_v3(state.as_ref(), pool, state_block)?,
+        }
+        .filter(|info| self.factories.is_empty() || self.factories.contains(&info.factory));
+        if info.is_none() {
+            tracing::debug!(%pool, ?kind, "Skipping pool not created by a configured factory");
+        }
+        if info.is_some() || state_block == block {
+            self.cache.insert(pool, info.clone());
+        }
+        Ok(info)
+    }
+
+    /// 代币的 decimals()（调用失败或返回值超出 u8 时为 None），按代币缓存
+    pub fn token_decimals(&mut self, token: Address, block: u64) -> Result<Option<u8>> {
+        if let Some(decimals) = self.decimals.get(&token) {
+            return Ok(*decimals);
+        }
+        let (state, state_block) = self.state(block)?;
+        let decimals =
+            self.call_words(state.as_ref(), state_block, token, &[DECIMALS])?[0].and_then(|w| u8::try_from(w).ok());
+        if decimals.is_some() || state_block == block {
+            self.decimals.insert(token, decimals);
+        }
+        Ok(decimals)
+    }
+
+    /// `block` 执行后的状态及其实际区块；历史状态被裁剪时退回最新状态并告警
+    fn state(&self, block: u64) -> Result<(StateProviderBox, u64)> {
+        match self.db.state_at_block(block) {
+            Ok(state) => Ok((state, block)),
+            Err(e) if e.is_pruned() => {
+                let latest = self.db.latest_block;
+                if !self.fallback_warned.replace(true) {
+                    tracing::warn!(block, latest, "History state pruned, resolving pool metadata at latest state");
+                }
+                Ok((self.db.state_at_block(latest)?, latest))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// 在 `block` 的环境下依次只读调用 `to` 的无参函数，返回单字返回值（失败或长度不符为 None）
+    fn call_words(
+        &mut self,
+        state: &dyn StateProvider,
+        block: u64,
+        to: Address,
+        selectors: &[[u8; 4]],
+    ) -> Result<Vec<Option<U256>>> {
+        let header = match self.header.take() {
+            Some(header) if header.number == block => header,
+            _ => {
+                let provider = match self.provider.take() {
+                    Some(provider) => provider,
+                    None => self.db.provider()?,
+                };
+                let header = provider
+                    .header_by_number(block)?
+                    .ok_or_else(|| AppError::NotFound(format!("header of block {block}")))?;
+                self.provider = Some(provider);
+                header
+            }
+        };
+        let mut evm = self.evm_config.evm_with_env(StateProviderDatabase::new(state), self.evm_config.evm_env(&header));
+        let words: Result<Vec<_>> = selectors
+            .iter()
+            .map(|selector| {
+                let result = evm
+                    .transact_system_call(Address::ZERO, to, Bytes::copy_from_slice(selector))
+                    .map_err(|e| AppError::Evm(format!("call {to:#x}: {e}")))?
+                    .result;
+                Ok(result.output().filter(|out| result.is_success() && out.len() == 32).map(|out| U256::from_be_slice(out)))
+            })
+            .collect();
+        drop(evm);
+        self.header = Some(header);
+        words
+    }
+
+    fn resolve_v2(&self, state: &dyn StateProvider, pool: Address) -> Result<Option<PoolInfo>> {
+        let slot = |slot: u64| -> Result<Option<Address>> {
+            let value = state.storage(pool, U256::from(slot).into())?.unwrap_or_default();
+            Ok((!value.is_zero()).then(|| Address::from_word(value.into())))
+        };
+        let (Some(factory), Some(token0), Some(token1)) =
+            (slot(V2_FACTORY_SLOT)?, slot(V2_TOKEN0_SLOT)?, slot(V2_TOKEN1_SLOT)?)
+        else {
+            return Ok(None);
+        };
+        Ok(Some(PoolInfo { pool, kind: PoolKind::V2, factory, token0, token1, fee: V2_FEE }))
+    }
+
+    fn resolve_v3(&mut self, state: &dyn StateProvider, pool: Address, block: u64) -> Result<Option<PoolInfo>> {
+        let words = self.call_words(state, block, pool, &[V3_FACTORY, V3_TOKEN0, V3_TOKEN1, V3_FEE])?;
+        let [Some(factory), Some(token0), Some(token1), Some(fee)] = words[..] else {
+            return Ok(None);
+        };
+        let address = |word: U256| Address::from_word(word.into());
+        Ok(Some(PoolInfo {
+            pool,
+            kind: PoolKind::V3,
+            factory: address(factory),
+            token0: address(token0),
+            token1: address(token1),
+            fee: u32::try_from(fee).unwrap_or(u32::MAX),
+        }))
+    }
+}
+
+/// 归一化的一笔兑换
+#[derive(Debug, Clone, Serialize)]
+pub struct SwapRow {
+    pub block_number: u64,
+    pub block_timestamp: u64,
+    pub transaction_hash: B256,
+    pub log_index: u64,
+    pub pool: Address,
+    pub kind: PoolKind,
+    pub fee: u32,
+    pub token0: Address,
+    pub token1: Address,
+    pub token_in: Address,
+    pub token_out: Address,
+    pub amount_in: U256,
+    pub amount_out: U256,
+    pub sender: Address,
+    pub recipient: Address,
+    /// V2：同一交易中紧邻的 Sync 给出的兑换后储备
+    pub reserve0: Option<U256>,
+    pub reserve1: Option<U256>,
+    /// V3：兑换后的价格、流动性与 tick
+    pub sqrt_price_x96: Option<U256>,
+    pub liquidity: Option<u128>,
+    pub tick: Option<i32>,
+}
+
+impl Record for SwapRow {
+    fn text(&self) -> String {
+        format!(
+            "block={} tx={:#x} log_index={} {:?} pool={:#x} {} {:#x} -> {} {:#x} sender={:#x} recipient={:#x}",
+            self.block_number,
+            self.transaction_hash,
+            self.log_index,
+            self.kind,
+            self.pool,
+            self.amount_in,
+            self.token_in,
+            self.amount_out,
+            self.token_out,
+            self.sender,
+            self.recipient,
+        )
+    }
+}
+
+impl SwapRow {
+    fn from_event(log: &LogRow, info: &PoolInfo, event: &PoolEvent, reserves: Option<(U256, U256)>) -> Option<Self> {
+        let (zero_for_one, amount_in, amount_out, sender, recipient) = match *event {
+            PoolEvent::V2Swap { sender, to, amount0_in, amount1_in, amount0_out, amount1_out } => {
+                // 两侧都可能既有转入又有转出（闪电兑换），按每个代币的净流入判断方向：
+                // token0 净流入池子即 0 -> 1
+                let zero_for_one = amount0_in > amount0_out;
+                let (amount_in, amount_out) = if zero_for_one {
+                    (amount0_in - amount0_out, amount1_out.saturating_sub(amount1_in))
+                } else {
+                    (amount1_in.saturating_sub(amount1_out), amount0_out - amount0_in)
+                };
+                (zero_for_one, amount_in, amount_out, sender, to)
+            }
+            PoolEvent::V3Swap { sender, recipient, amount0, amount1, .. } => {
+                let zero_for_one = amount0.is_positive();
+                let (amount_in, amount_out) = if zero_for_one {
+                    (amount0.unsigned_abs(), amount1.unsigned_abs())
+                } else {
+                    (amount1.unsigned_abs(), amount0.unsigned_abs())
+                };
+                (zero_for_one, amount_in, amount_out, sender, recipient)
+            }
+            PoolEvent::V2Sync { .. } => return None,
+        };
+        let (token_in, token_out) = if zero_for_one { (info.token0, info.token1) } else { (info.token1, info.token0) };
+        let v3 = match *event {
+            PoolEvent::V3Swap { sqrt_price_x96, liquidity, tick, .. } => Some((sqrt_price_x96, liquidity, tick)),
+            _ => None,
+        };
+        Some(Self {
+            block_number: log.block_number,
+            block_timestamp: log.block_timestamp,
+            transaction_hash: log.transaction_hash,
+            log_index: log.log_index,
+            pool: info.pool,
+            kind: info.kind,
+            fee: info.fee,
+            token0: info.token0,
+            token1: info.token1,
+            token_in,
+            token_out,
+            amount_in,
+            amount_out,
+            sender,
+            recipient,
+            reserve0: reserves.map(|r| r.0),
+            reserve1: reserves.map(|r| r.1),
+            sqrt_price_x96: v3.map(|v| v.0),
+            liquidity: v3.map(|v| v.1),
+            tick: v3.map(|v| v.2),
+        })
+    }
+}
+
+/// 池子范围（兑换提取与价格序列共用）
+#[derive(Debug, Clone)]
+pub struct PoolFilter {
+    /// 只提取这些池子（为空表示全部）
+    pub pools: Vec<Address>,
+    /// 只接受这些工厂创建的池子（为空表示不检查）
+    pub factories: Vec<Address>,
+}
+
+impl Default for PoolFilter {
+    fn default() -> Self {
+        Self { pools: Vec::new(), factories: vec![PANCAKE_V2_FACTORY, PANCAKE_V3_FACTORY] }
+    }
+}
+
+/// 扫描区块范围内的 PancakeSwap V2/V3 兑换，按 (区块, log_index) 顺序回调
+pub fn scan_swaps(
+    db: &BscDatabase,
+    range: Range<u64>,
+    filter: PoolFilter,
+    engine: &ParallelScan,
+    checkpointer: Option<&Checkpointer>,
+    resume: bool,
+    mut on_swap: impl FnMut(SwapRow) -> Result<()>,
+) -> Result<()> {
+    let mut resolver = PoolResolver::new(db, filter.factories);
+    let log_filter = LogFilter {
+        addresses: filter.pools,
+        topics: [vec![V2_SWAP_TOPIC, V2_SYNC_TOPIC, V3_SWAP_TOPIC], vec![], vec![], vec![]],
+    };
+    // V2 的 _update 先发 Sync 再发 Swap：记住上一条 Sync 以附上兑换后储备
+    let mut last_sync: Option<(u64, u64, Address, U256, U256)> = None;
+    // 解析器复用的 provider 只在同一区块内保留
+    let mut resolver_block = None;
+    scan_logs(db, range, log_filter, engine, checkpointer, resume, |log| {
+        let Some(event) = PoolEvent::from_log(&log.topics, &log.data) else { return Ok(()) };
+        if let PoolEvent::V2Sync { reserve0, reserve1 } = event {
+            last_sync = Some((log.block_number, log.log_index, log.address, reserve0, reserve1));
+            return Ok(());
+        }
+        if resolver_block != Some(log.block_number) {
+            resolver.refresh();
+            resolver_block = Some(log.block_number);
+        }
+        let Some(info) = resolver.resolve(log.address, event.kind(), log.block_number)? else { return Ok(()) };
+        let reserves = last_sync
+            .filter(|&(block, index, pool, ..)| {
+                block == log.block_number && index + 1 == log.log_index && pool == log.address
+            })
+            .map(|(.., r0, r1)| (r0, r1));
+        match SwapRow::from_event(&log, &info, &event, reserves) {
+            Some(row) => on_swap(row),
+            None => Ok(()),
+        }
+    })
+}
+
+/// 返回 `range`（左闭右开）内全部 PancakeSwap 兑换
+pub fn swaps(db: &BscDatabase, range: Range<u64>, filter: PoolFilter) -> Result<Vec<SwapRow>> {
+    ParallelScan::default().collect_with(|engine, push| scan_swaps(db, range, filter, engine, None, false, push))
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::keccak256;
+
+    use super::*;
+    use crate::test_support::{e18, log, v2_pool, v3_pool, words, BUSD, ROUTER, USDT, V2_PAIR, V3_POOL, WBNB};
+
+    fn v2_swap(amount0_in: U256, amount1_in: U256, amount0_out: U256, amount1_out: U256) -> PoolEvent {
+        PoolEvent::V2Swap { sender: ROUTER, to: ROUTER, amount0_in, amount1_in, amount0_out, amount1_out }
+    }
+
+    fn v3_swap(amount0: i64, amount1: i64) -> PoolEvent {
+        PoolEvent::V3Swap {
+            sender: ROUTER,
+            recipient: ROUTER,
+            amount0: I256::try_from(amount0).unwrap(),
+            amount1: I256::try_from(amount1).unwrap(),
+            sqrt_price_x96: U256::from(1) << 91,
+            liquidity: 1,
+            tick: -69_325,
+        }
+    }
+
+    fn signed(value: i128) -> U256 {
+        I256::try_from(value).unwrap().into_raw()
+    }
+
+    #[test]
+    fn event_topics_are_event_hashes() {
+        assert_eq!(V2_SWAP_TOPIC, keccak256("Swap(address,uint256,uint256,uint256,uint256,address)"));
+        assert_eq!(V2_SYNC_TOPIC, keccak256("Sync(uint112,uint112)"));
+        let v3 = "Swap(address,address,int256,int256,uint160,uint128,int24,uint128,uint128)";
+        assert_eq!(V3_SWAP_TOPIC, keccak256(v3));
+    }
+
+    #[test]
+    fn decodes_v2_swap_and_sync() {
+        // 600 BUSD 换出 1 WBNB
+        let data = words(&[U256::ZERO, e18(600), e18(1), U256::ZERO]);
+        let topics = [V2_SWAP_TOPIC, ROUTER.into_word(), ROUTER.into_word()];
+        assert_eq!(PoolEvent::from_log(&topics, &data), Some(v2_swap(U256::ZERO, e18(600), e18(1), U256::ZERO)));
+
+        let sync = PoolEvent::from_log(&[V2_SYNC_TOPIC], &words(&[e18(10_000), e18(6_000_000)])).expect("sync");
+        assert_eq!(sync, PoolEvent::V2Sync { reserve0: e18(10_000), reserve1: e18(6_000_000) });
+        assert_eq!(sync.kind(), PoolKind::V2);
+    }
+
+    #[test]
+    fn rejects_malformed_topic_counts_and_lengths() {
+        let data = words(&[U256::ZERO, e18(600), e18(1), U256::ZERO]);
+        let topics = [V2_SWAP_TOPIC, ROUTER.into_word(), ROUTER.into_word()];
+        assert_eq!(PoolEvent::from_log(&topics, &data[..96]), None);
+        assert_eq!(PoolEvent::from_log(&topics[..2], &data), None);
+        assert_eq!(PoolEvent::from_log(&[], &data), None);
+
+        // 同名但 indexed 布局不同的 Sync（非 PancakeSwap 池子）
+        let reserves = words(&[e18(1), e18(1)]);
+        assert_eq!(PoolEvent::from_log(&[V2_SYNC_TOPIC, ROUTER.into_word()], &reserves), None);
+
+        // Uniswap V3 的 Swap 没有协议费字段，topic0 不同，长度也不符
+        let uniswap = keccak256("Swap(address,address,int256,int256,uint160,uint128,int24)");
+        let v3 = words(&[U256::ZERO; 5]);
+        assert_eq!(PoolEvent::from_log(&[uniswap, ROUTER.into_word(), ROUTER.into_word()], &v3), None);
+        assert_eq!(PoolEvent::from_log(&[V3_SWAP_TOPIC, ROUTER.into_word(), ROUTER.into_word()], &v3), None);
+    }
+
+    #[test]
+    fn decodes_v3_swap_with_negative_amount_and_tick() {
+        // 池子收到 1000 USDT、付出 0.9765625 WBNB；负数按补码符号扩展到 32 字节
+        let sqrt_price = U256::from(1) << 91;
+        let data = words(&[
+            signed(1_000_000_000_000_000_000_000),
+            signed(-976_562_500_000_000_000),
+            sqrt_price,
+            U256::from(12_345_678_901_234_567_890u128),
+            signed(-69_325),
+            U256::ZERO,
+            U256::ZERO,
+        ]);
+        let topics = [V3_SWAP_TOPIC, ROUTER.into_word(), ROUTER.into_word()];
+        let event = PoolEvent::from_log(&topics, &data).expect("v3 swap");
+        let PoolEvent::V3Swap { amount0, amount1, sqrt_price_x96, liquidity, tick, .. } = event else {
+            panic!("expected a V3 swap, got {event:?}");
+        };
+        assert_eq!(amount0, I256::from_raw(e18(1000)));
+        assert_eq!(amount1, -I256::from_raw(U256::from(976_562_500_000_000_000u64)));
+        assert_eq!((sqrt_price_x96, liquidity, tick), (sqrt_price, 12_345_678_901_234_567_890, -69_325));
+        assert_eq!(event.kind(), PoolKind::V3);
+    }
+
+    #[test]
+    fn rejects_v3_swap_with_out_of_range_liquidity_or_tick() {
+        let topics = [V3_SWAP_TOPIC, ROUTER.into_word(), ROUTER.into_word()];
+        let data = |liquidity: U256, tick: U256| {
+            words(&[signed(1), signed(-1), U256::from(1) << 96, liquidity, tick, U256::ZERO, U256::ZERO])
+        };
+        assert!(PoolEvent::from_log(&topics, &data(U256::from(1), signed(-887_272))).is_some());
+        assert_eq!(PoolEvent::from_log(&topics, &data(U256::from(1) << 128, signed(0))), None);
+        assert_eq!(PoolEvent::from_log(&topics, &data(U256::from(1), signed(1 << 40))), None);
+    }
+
+    #[test]
+    fn v2_direction_follows_net_flows() {
+        let swap_log = log(V2_PAIR, &[], &[]);
+        let info = v2_pool();
+        let row = |event: PoolEvent, reserves| SwapRow::from_event(&swap_log, &info, &event, reserves);
+
+        // 普通兑换：600 BUSD -> 1 WBNB，以及反方向
+        let buy = row(v2_swap(U256::ZERO, e18(600), e18(1), U256::ZERO), None).unwrap();
+        assert_eq!((buy.token_in, buy.token_out, buy.amount_in, buy.amount_out), (BUSD, WBNB, e18(600), e18(1)));
+        let sell = row(v2_swap(e18(1), U256::ZERO, U256::ZERO, e18(599)), None).unwrap();
+        assert_eq!((sell.token_in, sell.token_out, sell.amount_in, sell.amount_out), (WBNB, BUSD, e18(1), e18(599)));
+
+        // 闪电兑换：借出 1 WBNB、还回 2 WBNB 并换出 600 BUSD；amount0_out 非零但方向是 0 -> 1
+        let flash = row(v2_swap(e18(2), U256::ZERO, e18(1), e18(600)), Some((e18(10_001), e18(5_999_400)))).unwrap();
+        assert_eq!((flash.token_in, flash.token_out), (WBNB, BUSD));
+        assert_eq!((flash.amount_in, flash.amount_out), (e18(1), e18(600)));
+        assert_eq!((flash.reserve0, flash.reserve1), (Some(e18(10_001)), Some(e18(5_999_400))));
+
+        assert!(row(PoolEvent::V2Sync { reserve0: e18(1), reserve1: e18(1) }, None).is_none());
+    }
+
+    #[test]
+    fn v3_direction_follows_amount_signs() {
+        let swap_log = log(V3_POOL, &[], &[]);
+
+        // 池子收到 token0（正）、付出 token1（负）
+        let row = SwapRow::from_event(&swap_log, &v3_pool(), &v3_swap(1_000, -2), None).unwrap();
+        assert_eq!((row.token_in, row.token_out), (USDT, WBNB));
+        assert_eq!((row.amount_in, row.amount_out), (U256::from(1_000), U256::from(2)));
+        assert_eq!((row.tick, row.reserve0), (Some(-69_325), None));
+
+        let row = SwapRow::from_event(&swap_log, &v3_pool(), &v3_swap(-1_000, 2), None).unwrap();
+        assert_eq!((row.token_in, row.token_out), (WBNB, USDT));
+        assert_eq!((row.amount_in, row.amount_out), (U256::from(2), U256::from(1_000)));
+    }
+}
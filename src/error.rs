@@ -1,17 +1,90 @@
+use reth_storage_errors::{db::DatabaseError, provider::ProviderError};
 use thiserror::Error;
 
+use crate::info::DataKind;
+
 /// 统一错误类型
 #[derive(Debug, Error)]
 pub enum AppError {
-	#[error(transparent)]
-	Eyre(#[from] eyre::Report),
-
 	#[error("Invalid argument: {0}")]
 	InvalidArg(String),
 
 	#[error("Not found: {0}")]
 	NotFound(String),
+
+	/// 数据存在过但已被节点裁剪（区别于不存在）
+	#[error("Data pruned for block {block} ({kind})")]
+	Pruned { kind: DataKind, block: u64 },
+
+	/// 请求的区块超过数据库最新块
+	#[error("Block {block} out of range (latest is {latest})")]
+	OutOfRange { block: u64, latest: u64 },
+
+	/// 数据损坏或解码失败（签名无法恢复、长度不一致等）
+	#[error("Corrupt data: {0}")]
+	Corrupt(String),
+
+	#[error("Database error: {0}")]
+	Database(ProviderError),
+
+	#[error("Static file error: {0}")]
+	StaticFile(String),
+
+	#[error("EVM execution failed: {0}")]
+	Evm(String),
+
+	#[error("I/O error: {0}")]
+	Io(#[from] std::io::Error),
+
+	#[error(transparent)]
+	Eyre(#[from] eyre::Report),
 }
 
-pub type Result<T> = std::result::Result<T, AppError>;
+impl AppError {
+	pub fn is_pruned(&self) -> bool {
+		matches!(self, AppError::Pruned { .. })
+	}
+
+	pub fn is_not_found(&self) -> bool {
+		matches!(self, AppError::NotFound(_))
+	}
+
+	/// CLI 退出码；被 eyre 包装的 AppError 按内部错误计算
+	pub fn exit_code(&self) -> i32 {
+		match self {
+			AppError::InvalidArg(_) => 2,
+			AppError::NotFound(_) => 3,
+			AppError::Pruned { .. } => 4,
+			AppError::OutOfRange { .. } => 5,
+			AppError::Corrupt(_) => 6,
+			AppError::Database(_) => 7,
+			AppError::StaticFile(_) => 8,
+			AppError::Evm(_) => 9,
+			AppError::Io(_) => 10,
+			AppError::Eyre(report) => report.downcast_ref::<AppError>().map_or(1, AppError::exit_code),
+		}
+	}
+}
 
+impl From<ProviderError> for AppError {
+	fn from(e: ProviderError) -> Self {
+		match e {
+			ProviderError::StateAtBlockPruned(block) => AppError::Pruned { kind: DataKind::AccountHistory, block },
+			ProviderError::HeaderNotFound(id) => AppError::NotFound(format!("header {id:?}")),
+			ProviderError::BlockBodyIndicesNotFound(block) => AppError::NotFound(format!("block body {block}")),
+			ProviderError::NippyJar(msg) => AppError::StaticFile(msg),
+			e @ (ProviderError::MissingStaticFileBlock(..) | ProviderError::MissingStaticFileTx(..)) => {
+				AppError::StaticFile(e.to_string())
+			}
+			other => AppError::Database(other),
+		}
+	}
+}
+
+impl From<DatabaseError> for AppError {
+	fn from(e: DatabaseError) -> Self {
+		AppError::Database(ProviderError::Database(e))
+	}
+}
+
+pub type Result<T> = std::result::Result<T, AppError>;
@@ -0,0 +1,290 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use alloy_json_abi::{Event, JsonAbi};
+use alloy_primitives::B256;
+use eyre::Context;
+
+use crate::abi::{decode_event, AbiRegistry, DecodedEvent};
+use crate::error::{AppError, Result};
+use crate::signatures::{parse_csv, parse_json};
+
+/// 内置的常用事件声明（带 indexed 与参数名，用于解码）
+const BUNDLED_EVENTS: &[&str] = &[
+    // BEP-20
+    "Transfer(address indexed from, address indexed to, uint256 value)",
+    "Approval(address indexed owner, address indexed spender, uint256 value)",
+    // BEP-721
+    "Transfer(address indexed from, address indexed to, uint256 indexed tokenId)",
+    "Approval(address indexed owner, address indexed approved, uint256 indexed tokenId)",
+    "ApprovalForAll(address indexed owner, address indexed operator, bool approved)",
+    // BEP-1155
+    "TransferSingle(address indexed operator, address indexed from, address indexed to, uint256 id, uint256 value)",
+    "TransferBatch(address indexed operator, address indexed from, address indexed to, uint256[] ids, uint256[] values)",
+    "URI(string value, uint256 indexed id)",
+    // WBNB
+    "Deposit(address indexed dst, uint256 wad)",
+    "Withdrawal(address indexed src, uint256 wad)",
+    // PancakeSwap V2 Factory / Pair
+    "PairCreated(address indexed token0, address indexed token1, address pair, uint256 index)",
+    "Swap(address indexed sender, uint256 amount0In, uint256 amount1In, uint256 amount0Out, uint256 amount1Out, address indexed to)",
+    "Sync(uint112 reserve0, uint112 reserve1)",
+    "Mint(address indexed sender, uint256 amount0, uint256 amount1)",
+    "Burn(address indexed sender, uint256 amount0, uint256 amount1, address indexed to)",
+    // PancakeSwap V3 Factory / Pool
+    "PoolCreated(address indexed token0, address indexed token1, uint24 indexed fee, int24 tickSpacing, address pool)",
+    "Initialize(uint160 sqrtPriceX96, int24 tick)",
+    "Swap(address indexed sender, address indexed recipient, int256 amount0, int256 amount1, uint160 sqrtPriceX96, uint128 liquidity, int24 tick, uint128 protocolFeesToken0, uint128 protocolFeesToken1)",
+    "Mint(address sender, address indexed owner, int24 indexed tickLower, int24 indexed tickUpper, uint128 amount, uint256 amount0, uint256 amount1)",
+    "Burn(address indexed owner, int24 indexed tickLower, int24 indexed tickUpper, uint128 amount, uint256 amount0, uint256 amount1)",
+    "Collect(address indexed owner, address recipient, int24 indexed tickLower, int24 indexed tickUpper, uint128 amount0, uint128 amount1)",
+    // Venus vToken / Comptroller
+    "Mint(address minter, uint256 mintAmount, uint256 mintTokens)",
+    "Redeem(address redeemer, uint256 redeemAmount, uint256 redeemTokens)",
+    "Borrow(address borrower, uint256 borrowAmount, uint256 accountBorrows, uint256 totalBorrows)",
+    "RepayBorrow(address payer, address borrower, uint256 repayAmount, uint256 accountBorrows, uint256 totalBorrows)",
+    "LiquidateBorrow(address liquidator, address borrower, uint256 repayAmount, address vTokenCollateral, uint256 seizeTokens)",
+    "AccrueInterest(uint256 cashPrior, uint256 interestAccumulated, uint256 borrowIndex, uint256 totalBorrows)",
+    "MarketEntered(address vToken, address account)",
+    "MarketExited(address vToken, address account)",
+    // BSC 系统合约：ValidatorSet / SlashIndicator / SystemReward / StakeHub / TokenHub
+    "validatorSetUpdated()",
+    "validatorDeposit(address indexed validator, uint256 amount)",
+    "deprecatedDeposit(address indexed validator, uint256 amount)",
+    "validatorFelony(address indexed validator, uint256 amount)",
+    "validatorJailed(address indexed validator)",
+    "systemTransfer(uint256 amount)",
+    "finalityRewardDeposit(address indexed validator, uint256 amount)",
+    "deprecatedFinalityRewardDeposit(address indexed validator, uint256 amount)",
+    "validatorSlashed(address indexed validator)",
+    "maliciousVoteSlashed(bytes32 indexed voteAddrSlice)",
+    "rewardTo(address indexed to, uint256 amount)",
+    "receiveDeposit(address indexed from, uint256 amount)",
+    "ValidatorCreated(address indexed consensusAddress, address indexed operatorAddress, address indexed creditContract, bytes voteAddress)",
+    "Delegated(address indexed operatorAddress, address indexed delegator, uint256 shares, uint256 bnbAmount)",
+    "Undelegated(address indexed operatorAddress, address indexed delegator, uint256 shares, uint256 bnbAmount)",
+    "Redelegated(address indexed srcValidator, address indexed dstValidator, address indexed delegator, uint256 oldShares, uint256 newShares, uint256 bnbAmount)",
+    "RewardDistributed(address indexed operatorAddress, uint256 reward)",
+    "ValidatorSlashed(address indexed operatorAddress, uint256 jailUntil, uint256 slashAmount, uint8 slashType)",
+    "transferInSuccess(address bep20Addr, address refundAddr, uint256 amount)",
+];
+
+/// 日志的单行文本：优先解码结果，其次事件签名，最后原始 topics
+pub fn log_text(decoded: Option<&DecodedEvent>, event: Option<&str>, topics: &[B256], data: &[u8]) -> String {
+    match (decoded, event) {
+        (Some(decoded), _) => decoded.to_string(),
+        (None, Some(label)) => format!("{label} topics={} data={} bytes", topics.len(), data.len()),
+        (None, None) => format!("topics={topics:?} data={} bytes", data.len()),
+    }
+}
+
+/// 本地事件签名库：topic0 -> 候选事件声明。
+///
+/// 同一 topic0 可能对应 indexed 布局不同的多个声明（如 BEP-20 与 BEP-721 的 Transfer），
+/// 解码时按 topic 数量逐个尝试。
+#[derive(Debug, Clone, Default)]
+pub struct EventRegistry {
+    map: HashMap<B256, Vec<Event>>,
+}
+
+impl EventRegistry {
+    /// 仅含内置事件
+    pub fn bundled() -> Self {
+        let mut registry = Self::default();
+        for sig in BUNDLED_EVENTS {
+            registry.insert(sig).expect("bundled event signature must parse");
+        }
+        registry
+    }
+
+    /// 插入事件声明（可带 `event` 前缀、indexed 与参数名），返回其 topic0
+    pub fn insert(&mut self, signature: &str) -> Result<B256> {
+        let event = Event::parse(signature.trim())
+            .map_err(|e| AppError::InvalidArg(format!("event signature {signature:?}: {e}")))?;
+        let topic = event.selector();
+        let entry = self.map.entry(topic).or_default();
+        if !entry.contains(&event) {
+            entry.push(event);
+        }
+        Ok(topic)
+    }
+
+    /// 导入事件签名文件（格式同函数签名库，key 为 32 字节 topic0）：
+    /// - `.json`：`{"0xddf2...": "Transfer(address indexed from, ...)"}`（值也可为数组），或声明字符串数组
+    /// - `.csv`：每行 `topic0,signature` 或仅 `signature`，允许表头
+    ///
+    /// 无法解析或声明的 topic0 与签名哈希不符的条目会被跳过。返回导入条数。
+    pub fn import(&mut self, path: &Path) -> Result<usize> {
+        let raw = std::fs::read_to_string(path).with_context(|| format!("read event signature file {}", path.display()))?;
+        let entries: Vec<(Option<String>, String)> = match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => parse_json(&raw).map_err(|e| {
+                AppError::InvalidArg(format!("event signature file {}: {e}", path.display()))
+            })?,
+            Some("csv") => parse_csv(&raw, 32),
+            _ => {
+                return Err(AppError::InvalidArg(format!(
+                    "event signature file {} must have a .json or .csv extension",
+                    path.display()
+                )));
+            }
+        };
+
+        let (mut imported, mut skipped) = (0usize, 0usize);
+        for (declared, signature) in entries {
+            let Ok(event) = Event::parse(signature.trim()) else {
+                skipped += 1;
+                continue;
+            };
+            let matches = declared.as_deref().is_none_or(|d| d.parse::<B256>().is_ok_and(|t| t == event.selector()));
+            if matches && self.insert(&signature).is_ok() {
+                imported += 1;
+            } else {
+                skipped += 1;
+            }
+        }
+        if skipped > 0 {
+            tracing::warn!(file = %path.display(), skipped, "Skipped unparsable or mismatched event signatures");
+        }
+        tracing::info!(file = %path.display(), imported, "Imported event signatures");
+        Ok(imported)
+    }
+
+    /// 查找 topic0 的候选事件声明
+    pub fn lookup(&self, topic0: B256) -> &[Event] {
+        self.map.get(&topic0).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// topic0 的文本签名（不同签名碰撞时以 ` | ` 分隔）
+    pub fn label(&self, topic0: B256) -> Option<String> {
+        let mut names: Vec<String> = Vec::new();
+        for event in self.lookup(topic0) {
+            let sig = event.signature();
+            if !names.contains(&sig) {
+                names.push(sig);
+            }
+        }
+        (!names.is_empty()).then(|| names.join(" | "))
+    }
+
+    /// 按 topic0 尽力解码：只尝试 indexed 数量与 topic 数量一致的候选，取第一个成功的
+    pub fn decode(&self, topics: &[B256], data: &[u8]) -> Option<DecodedEvent> {
+        let topic0 = *topics.first()?;
+        self.lookup(topic0)
+            .iter()
+            .filter(|e| e.num_topics() == topics.len())
+            .find_map(|e| decode_event(e, topics, data))
+    }
+
+    /// 标注并解码一条日志：优先使用合约 ABI，其次按签名库解码；返回 (事件签名, 解码结果)
+    pub fn describe_log(&self, abi: Option<&JsonAbi>, topics: &[B256], data: &[u8]) -> (Option<String>, Option<DecodedEvent>) {
+        let decoded = abi
+            .and_then(|abi| AbiRegistry::decode_log(abi, topics, data))
+            .or_else(|| self.decode(topics, data));
+        let label = match &decoded {
+            Some(event) => Some(event.signature.clone()),
+            None => topics.first().and_then(|t| self.label(*t)),
+        };
+        (label, decoded)
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.values().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::{hex, keccak256};
+    use serde_json::Value;
+
+    use super::*;
+    use crate::test_support::{temp_dir, ROUTER, V2_PAIR};
+
+    const TRANSFER: &str = "Transfer(address,address,uint256)";
+    const V2_SWAP: &str = "Swap(address,uint256,uint256,uint256,uint256,address)";
+
+    fn param<'a>(event: &'a DecodedEvent, name: &str) -> &'a Value {
+        &event.params.iter().find(|p| p.name == name).expect("param").value
+    }
+
+    #[test]
+    fn bundled_signatures_all_parse() {
+        let registry = EventRegistry::bundled();
+        assert_eq!(registry.len(), BUNDLED_EVENTS.len());
+    }
+
+    #[test]
+    fn decodes_by_topic_count_when_topic0_collides() {
+        let registry = EventRegistry::bundled();
+        let transfer = keccak256(TRANSFER);
+        assert_eq!(registry.lookup(transfer).len(), 2);
+
+        // BEP-20：value 在 data 中
+        let amount = hex!("0000000000000000000000000000000000000000000000000de0b6b3a7640000");
+        let topics = [transfer, V2_PAIR.into_word(), ROUTER.into_word()];
+        let erc20 = registry.decode(&topics, &amount).expect("bep-20 transfer");
+        assert_eq!(erc20.signature, TRANSFER);
+        assert_eq!(param(&erc20, "from"), &Value::String(V2_PAIR.to_checksum(None)));
+        assert_eq!(param(&erc20, "to"), &Value::String(ROUTER.to_checksum(None)));
+        assert_eq!(param(&erc20, "value"), &Value::String("1000000000000000000".to_string()));
+
+        // ERC-721：tokenId indexed，data 为空
+        let topics = [transfer, V2_PAIR.into_word(), ROUTER.into_word(), B256::with_last_byte(42)];
+        let erc721 = registry.decode(&topics, &[]).expect("erc-721 transfer");
+        assert_eq!(param(&erc721, "tokenId"), &Value::String("42".to_string()));
+
+        // topic 数量与任何候选都不符
+        assert!(registry.decode(&[transfer, V2_PAIR.into_word()], &amount).is_none());
+        assert!(registry.decode(&[B256::ZERO], &[]).is_none());
+        assert!(registry.decode(&[], &amount).is_none());
+    }
+
+    #[test]
+    fn labels_undecodable_logs_by_signature() {
+        let registry = EventRegistry::bundled();
+        let swap = keccak256(V2_SWAP);
+        assert_eq!(registry.label(swap).as_deref(), Some(V2_SWAP));
+        assert_eq!(registry.label(keccak256(TRANSFER)).as_deref(), Some(TRANSFER));
+        assert_eq!(registry.label(B256::ZERO), None);
+
+        // 数据截断无法解码时仍给出签名标注，文本输出退回 topics 概要
+        let topics = [swap, ROUTER.into_word(), ROUTER.into_word()];
+        let (label, decoded) = registry.describe_log(None, &topics, &[0u8; 31]);
+        assert_eq!(label.as_deref(), Some(V2_SWAP));
+        assert!(decoded.is_none());
+        assert_eq!(log_text(None, label.as_deref(), &topics, &[0u8; 31]), format!("{V2_SWAP} topics=3 data=31 bytes"));
+    }
+
+    #[test]
+    fn insert_returns_topic0_and_deduplicates() {
+        let mut registry = EventRegistry::default();
+        let topic0 =
+            registry.insert("event Transfer(address indexed from, address indexed to, uint256 value)").unwrap();
+        assert_eq!(topic0, keccak256(TRANSFER));
+        registry.insert("Transfer(address indexed from, address indexed to, uint256 value)").unwrap();
+        assert_eq!(registry.len(), 1);
+        assert!(registry.insert("Transfer(address").is_err());
+    }
+
+    #[test]
+    fn import_skips_mismatched_topics() {
+        let dir = temp_dir("events-import");
+        let path = dir.join("events.json");
+        let (transfer, swap) = (keccak256(TRANSFER), keccak256(V2_SWAP));
+        // 第二项的 topic 与声明不符（Sync 的声明挂在 Swap 的 topic 下）
+        let json = format!(
+            r#"{{"{transfer:#x}": "Transfer(address indexed from, address indexed to, uint256 value)",
+                "{swap:#x}": "Sync(uint112 reserve0, uint112 reserve1)"}}"#
+        );
+        std::fs::write(&path, json).unwrap();
+        let mut registry = EventRegistry::default();
+        assert_eq!(registry.import(&path).unwrap(), 1);
+        assert_eq!(registry.lookup(transfer).len(), 1);
+        assert!(registry.lookup(swap).is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,18 +1,13 @@
-use std::sync::Arc;
-
 // bring signer recovery trait into scope for `recover_signer()`
 use alloy_consensus::transaction::SignerRecoverable;
-use eyre::{eyre, Result};
 use revm_inspectors::tracing::{TracingInspector, TracingInspectorConfig};
 use reth_ethereum::{
-    chainspec::ChainSpecBuilder,
     evm::{
         primitives::ConfigureEvm,
         EthEvmConfig,
         revm::{database::StateProviderDatabase, db::CacheDB},
     },
-    node::EthereumNode,
-    provider::{providers::ReadOnlyConfig, BlockReader},
+    provider::BlockReader,
 };
 // traits needed for evm transact and db commit
 use alloy_evm::Evm;
@@ -20,18 +15,42 @@ use reth::revm::DatabaseCommit;
 use reth_primitives::{Recovered};
 use reth::rpc::types::BlockHashOrNumber;
 
+use crate::databases::BscDatabase;
+use crate::error::{AppError, Result};
+use crate::output::Record;
+use alloy_primitives::{Address, B256};
+use serde::Serialize;
+
+/// evm_ct_test 输出：执行追踪中发现的 CREATE/CREATE2
+#[derive(Debug, Clone, Serialize)]
+pub struct NewContract {
+    pub block: u64,
+    pub tx_index: usize,
+    pub tx_hash: B256,
+    pub address: Address,
+    pub creator: Address,
+    pub init_code_len: usize,
+}
+
+impl Record for NewContract {
+    fn text(&self) -> String {
+        format!(
+            "new contract: addr={:?}, creator={:?}, init_code_len={}",
+            self.address, self.creator, self.init_code_len
+        )
+    }
+}
 
-pub fn evm_ct_test(block: u64, datadir: String) -> Result<()> {
-    // ProviderFactory
-    let spec = Arc::new(ChainSpecBuilder::mainnet().build());
-    let factory = EthereumNode::provider_factory_builder()
-        .open_read_only(spec.clone(), ReadOnlyConfig::from_datadir(&datadir))?;
-    let provider = factory.provider()?;
+/// 重放区块内全部交易，返回其中创建的合约（含内部 CREATE/CREATE2）
+pub fn evm_ct_test(db: &BscDatabase, block: u64) -> Result<Vec<NewContract>> {
+    // 分叉表决定 EVM 规则，必须与数据所属链一致
+    let spec = db.chain_spec.clone();
+    let provider = db.provider()?;
 
     // 读取区块 & 状态
     let block = provider
         .block(BlockHashOrNumber::Number(block))?
-        .ok_or_else(|| eyre!("block {} not found", block))?;
+        .ok_or_else(|| AppError::NotFound(format!("block {block}")))?;
     let state_provider = provider.history_by_block_hash(block.header.parent_hash)?;
     let mut db = CacheDB::new(StateProviderDatabase::new(state_provider.as_ref()));
 
@@ -44,14 +63,20 @@ pub fn evm_ct_test(block: u64, datadir: String) -> Result<()> {
     let mut inspector = TracingInspector::new(TracingInspectorConfig::default());
 
     // 执行区块内每笔交易
-    for tx in &block.body.transactions {
-        let recovered_tx =
-            Recovered::new_unchecked(tx.clone(), tx.recover_signer().unwrap());
+    let block_number = block.header.number;
+    let mut created = Vec::new();
+    for (tx_index, tx) in block.body.transactions.iter().enumerate() {
+        let signer = tx
+            .recover_signer()
+            .map_err(|e| AppError::Corrupt(format!("recover signer of tx {}: {e}", tx.hash())))?;
+        let recovered_tx = Recovered::new_unchecked(tx.clone(), signer);
         let tx_env = evm_config.tx_env(&recovered_tx);
 
         let mut evm =
             evm_config.evm_with_env_and_inspector(&mut db, evm_env.clone(), &mut inspector);
-        let result = evm.transact(tx_env)?;
+        let result = evm
+            .transact(tx_env)
+            .map_err(|e| AppError::Evm(format!("tx {}: {e}", tx.hash())))?;
 
         // 输出合约创建事件
         for node in inspector
@@ -61,17 +86,19 @@ pub fn evm_ct_test(block: u64, datadir: String) -> Result<()> {
             .filter(|n| n.trace.kind.is_any_create())
         {
             let t = &node.trace;
-            println!(
-                "new contract: addr={:?}, creator={:?}, init_code_len={}",
-                t.address,
-                t.caller,
-                t.data.len()
-            );
+            created.push(NewContract {
+                block: block_number,
+                tx_index,
+                tx_hash: *tx.hash(),
+                address: t.address,
+                creator: t.caller,
+                init_code_len: t.data.len(),
+            });
         }
 
         inspector.traces_mut().clear();
         db.commit(result.state);
     }
 
-    Ok(())
+    Ok(created)
 }
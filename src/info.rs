@@ -0,0 +1,352 @@
+use std::fmt::{self, Write as _};
+use std::path::Path;
+
+use eyre::Context;
+use reth_db::{cursor::DbCursorRO, tables, transaction::DbTx, Database};
+use reth_prune_types::PruneSegment;
+use reth_provider::{PruneCheckpointReader, StageCheckpointReader, TransactionsProvider};
+use reth_static_file_types::StaticFileSegment;
+use serde::Serialize;
+
+use crate::databases::{BscDatabase, BscProvider};
+use crate::error::Result;
+use crate::output::Record;
+
+/// 节点数据种类
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataKind {
+    Headers,
+    Bodies,
+    Senders,
+    Receipts,
+    AccountHistory,
+    StorageHistory,
+    AccountChangeSets,
+    StorageChangeSets,
+}
+
+impl DataKind {
+    pub const ALL: [DataKind; 8] = [
+        DataKind::Headers,
+        DataKind::Bodies,
+        DataKind::Senders,
+        DataKind::Receipts,
+        DataKind::AccountHistory,
+        DataKind::StorageHistory,
+        DataKind::AccountChangeSets,
+        DataKind::StorageChangeSets,
+    ];
+
+    /// 对应的裁剪段（无裁剪配置的种类返回 None）
+    pub fn prune_segment(self) -> Option<PruneSegment> {
+        match self {
+            DataKind::Senders => Some(PruneSegment::SenderRecovery),
+            DataKind::Receipts => Some(PruneSegment::Receipts),
+            DataKind::AccountHistory | DataKind::AccountChangeSets => Some(PruneSegment::AccountHistory),
+            DataKind::StorageHistory | DataKind::StorageChangeSets => Some(PruneSegment::StorageHistory),
+            DataKind::Headers | DataKind::Bodies => None,
+        }
+    }
+
+    /// 对应的 static_files 段
+    pub fn static_file_segment(self) -> Option<StaticFileSegment> {
+        match self {
+            DataKind::Headers => Some(StaticFileSegment::Headers),
+            DataKind::Bodies => Some(StaticFileSegment::Transactions),
+            DataKind::Senders => Some(StaticFileSegment::TransactionSenders),
+            DataKind::Receipts => Some(StaticFileSegment::Receipts),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for DataKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DataKind::Headers => "headers",
+            DataKind::Bodies => "bodies",
+            DataKind::Senders => "senders",
+            DataKind::Receipts => "receipts",
+            DataKind::AccountHistory => "account_history",
+            DataKind::StorageHistory => "storage_history",
+            DataKind::AccountChangeSets => "account_changesets",
+            DataKind::StorageChangeSets => "storage_changesets",
+        };
+        f.write_str(name)
+    }
+}
+
+/// 某类数据的可用区块区间（闭区间）；None 表示完全不可用
+#[derive(Debug, Clone, Serialize)]
+pub struct DataRange {
+    pub kind: DataKind,
+    pub first: Option<u64>,
+    pub last: Option<u64>,
+}
+
+impl DataRange {
+    pub fn contains(&self, block: u64) -> bool {
+        matches!((self.first, self.last), (Some(first), Some(last)) if first <= block && block <= last)
+    }
+}
+
+/// 裁剪检查点
+#[derive(Debug, Clone, Serialize)]
+pub struct PruneEntry {
+    pub segment: String,
+    pub mode: String,
+    pub block_number: Option<u64>,
+    pub tx_number: Option<u64>,
+}
+
+/// 同步阶段检查点
+#[derive(Debug, Clone, Serialize)]
+pub struct StageEntry {
+    pub stage: String,
+    pub block_number: u64,
+}
+
+/// 单个 static_files 文件
+#[derive(Debug, Clone, Serialize)]
+pub struct SegmentFile {
+    pub segment: String,
+    /// 文件名中的预期区块区间
+    pub expected_start: u64,
+    pub expected_end: u64,
+    /// 实际写入的区块/交易区间
+    pub block_range: Option<(u64, u64)>,
+    pub tx_range: Option<(u64, u64)>,
+}
+
+/// 数据目录可用性报告
+#[derive(Debug, Clone, Serialize)]
+pub struct DataAvailability {
+    pub chain_id: u64,
+    pub latest_block: u64,
+    pub ranges: Vec<DataRange>,
+    pub prune_checkpoints: Vec<PruneEntry>,
+    /// reth.toml 中的 [prune] 配置原文
+    pub prune_config: Option<String>,
+    pub stage_checkpoints: Vec<StageEntry>,
+    pub static_files: Vec<SegmentFile>,
+}
+
+impl DataAvailability {
+    pub fn range(&self, kind: DataKind) -> Option<&DataRange> {
+        self.ranges.iter().find(|r| r.kind == kind)
+    }
+}
+
+impl BscDatabase {
+    /// 生成数据可用性报告
+    pub fn availability(&self) -> Result<DataAvailability> {
+        let provider = self.provider()?;
+
+        let prune_checkpoints = provider
+            .get_prune_checkpoints()?
+            .into_iter()
+            .map(|(segment, cp)| PruneEntry {
+                segment: format!("{segment:?}"),
+                mode: format!("{:?}", cp.prune_mode),
+                block_number: cp.block_number,
+                tx_number: cp.tx_number,
+            })
+            .collect();
+
+        let stage_checkpoints = provider
+            .get_all_checkpoints()?
+            .into_iter()
+            .map(|(stage, cp)| StageEntry { stage, block_number: cp.block_number })
+            .collect();
+
+        let static_files = self.static_file_segments()?;
+
+        let mut ranges = Vec::with_capacity(DataKind::ALL.len());
+        for kind in DataKind::ALL {
+            ranges.push(self.data_range(&provider, kind, &static_files)?);
+        }
+
+        Ok(DataAvailability {
+            chain_id: self.chain_spec.chain.id(),
+            latest_block: self.latest_block,
+            ranges,
+            prune_checkpoints,
+            prune_config: read_prune_config(&self.config.datadir),
+            stage_checkpoints,
+            static_files,
+        })
+    }
+
+    /// 列出 static_files 目录下的全部段文件（按段、起始块排序）
+    pub fn static_file_segments(&self) -> Result<Vec<SegmentFile>> {
+        let dir = self.config.static_files_dir();
+        let sf = self.static_file_provider();
+        let mut files = Vec::new();
+        if !dir.is_dir() {
+            return Ok(files);
+        }
+        for entry in std::fs::read_dir(&dir).with_context(|| format!("list {}", dir.display()))? {
+            let name = entry?.file_name();
+            let Some((segment, range)) = name.to_str().and_then(StaticFileSegment::parse_filename) else {
+                continue;
+            };
+            let (block_range, tx_range) = match sf.get_segment_provider_from_block(segment, range.start(), None) {
+                Ok(jar) => {
+                    let header = jar.user_header();
+                    (
+                        header.block_range().map(|r| (r.start(), r.end())),
+                        header.tx_range().map(|r| (r.start(), r.end())),
+                    )
+                }
+                Err(e) => {
+                    tracing::warn!(?segment, start = range.start(), error = %e, "Static file not readable");
+                    (None, None)
+                }
+            };
+            files.push(SegmentFile {
+                segment: segment.as_str().to_string(),
+                expected_start: range.start(),
+                expected_end: range.end(),
+                block_range,
+                tx_range,
+            });
+        }
+        files.sort_by(|a, b| a.segment.cmp(&b.segment).then(a.expected_start.cmp(&b.expected_start)));
+        Ok(files)
+    }
+
+    fn data_range(&self, provider: &BscProvider, kind: DataKind, files: &[SegmentFile]) -> Result<DataRange> {
+        let tx = self.mdbx().tx()?;
+        let latest = self.latest_block;
+
+        // static_files 中该段的最低区块
+        let static_first = kind.static_file_segment().and_then(|segment| {
+            let segment = segment.as_str();
+            files.iter().filter(|f| f.segment == segment).filter_map(|f| f.block_range.map(|r| r.0)).min()
+        });
+
+        let tx_block = |tx_num: Option<u64>| -> Result<Option<u64>> {
+            Ok(match tx_num {
+                Some(n) => provider.transaction_block(n)?,
+                None => None,
+            })
+        };
+
+        let (first, last) = match kind {
+            DataKind::Headers => (Some(self.earliest_available_block), Some(latest)),
+            DataKind::Bodies => {
+                let db_first = tx.cursor_read::<tables::Transactions>()?.first()?.map(|(n, _)| n);
+                (min_opt(static_first, tx_block(db_first)?), Some(latest))
+            }
+            DataKind::Receipts => {
+                let db_first = tx.cursor_read::<tables::Receipts>()?.first()?.map(|(n, _)| n);
+                (min_opt(static_first, tx_block(db_first)?), Some(latest))
+            }
+            DataKind::Senders => {
+                let db_first = tx.cursor_read::<tables::TransactionSenders>()?.first()?.map(|(n, _)| n);
+                (min_opt(static_first, tx_block(db_first)?), Some(latest))
+            }
+            // 历史索引按 (地址/槽位, 分片最高块) 分片存储，没有按区块的起点可读；
+            // 索引非空时覆盖到最新块，起点由下方的裁剪检查点决定
+            DataKind::AccountHistory => {
+                let indexed = tx.cursor_read::<tables::AccountsHistory>()?.first()?.is_some();
+                (indexed.then_some(0), indexed.then_some(latest))
+            }
+            DataKind::StorageHistory => {
+                let indexed = tx.cursor_read::<tables::StoragesHistory>()?.first()?.is_some();
+                (indexed.then_some(0), indexed.then_some(latest))
+            }
+            DataKind::AccountChangeSets => {
+                let mut cursor = tx.cursor_read::<tables::AccountChangeSets>()?;
+                (cursor.first()?.map(|(b, _)| b), cursor.last()?.map(|(b, _)| b))
+            }
+            DataKind::StorageChangeSets => {
+                let mut cursor = tx.cursor_read::<tables::StorageChangeSets>()?;
+                (cursor.first()?.map(|(k, _)| k.block_number()), cursor.last()?.map(|(k, _)| k.block_number()))
+            }
+        };
+
+        // 裁剪检查点之前（含）的数据视为不可用
+        let pruned_to = match kind.prune_segment() {
+            Some(segment) => provider.get_prune_checkpoint(segment)?.and_then(|cp| cp.block_number),
+            None => None,
+        };
+        let first = match (first, pruned_to) {
+            (Some(f), Some(p)) => Some(f.max(p + 1)),
+            (f, _) => f,
+        };
+        let (first, last) = match (first, last) {
+            (Some(f), Some(l)) if f <= l => (Some(f), Some(l)),
+            _ => (None, None),
+        };
+        Ok(DataRange { kind, first, last })
+    }
+}
+
+fn min_opt(a: Option<u64>, b: Option<u64>) -> Option<u64> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// 读取 `<datadir>/reth.toml` 中的 [prune] 相关段落（不解析，仅原文展示）
+fn read_prune_config(datadir: &Path) -> Option<String> {
+    let raw = std::fs::read_to_string(datadir.join("reth.toml")).ok()?;
+    let mut out = Vec::new();
+    let mut in_prune = false;
+    for line in raw.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            in_prune = trimmed.trim_start_matches('[').starts_with("prune");
+        }
+        if in_prune && !trimmed.is_empty() {
+            out.push(line);
+        }
+    }
+    (!out.is_empty()).then(|| out.join("\n"))
+}
+
+impl Record for DataAvailability {
+    fn text(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "Chain id: {}", self.chain_id);
+        let _ = writeln!(out, "Latest block: {}", self.latest_block);
+        out.push_str("\nData ranges:\n");
+        for r in &self.ranges {
+            let _ = match (r.first, r.last) {
+                (Some(first), Some(last)) => writeln!(out, "  {:<20} {}..={}", r.kind.to_string(), first, last),
+                _ => writeln!(out, "  {:<20} unavailable", r.kind.to_string()),
+            };
+        }
+        out.push_str("\nPrune checkpoints:\n");
+        if self.prune_checkpoints.is_empty() {
+            out.push_str("  (none)\n");
+        }
+        for p in &self.prune_checkpoints {
+            let _ = writeln!(out, "  {:<20} mode={} block={:?} tx={:?}", p.segment, p.mode, p.block_number, p.tx_number);
+        }
+        if let Some(cfg) = &self.prune_config {
+            let _ = writeln!(out, "\nPrune config (reth.toml):\n{cfg}");
+        }
+        out.push_str("\nStage checkpoints:\n");
+        for s in &self.stage_checkpoints {
+            let _ = writeln!(out, "  {:<24} {}", s.stage, s.block_number);
+        }
+        out.push_str("\nStatic files:");
+        for f in &self.static_files {
+            let fmt_range = |r: Option<(u64, u64)>| r.map(|(a, b)| format!("{a}..={b}")).unwrap_or_else(|| "-".to_string());
+            let _ = write!(
+                out,
+                "\n  {:<14} {}..={} blocks={} txs={}",
+                f.segment,
+                f.expected_start,
+                f.expected_end,
+                fmt_range(f.block_range),
+                fmt_range(f.tx_range),
+            );
+        }
+        out
+    }
+}
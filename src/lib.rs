@@ -1,19 +1,42 @@
 pub use reth_provider::{HeaderProvider, TransactionsProvider, StateProvider, ReceiptProvider};
 
+pub mod chain;
+pub mod blocks;
 pub mod databases;
 pub mod cli;
 pub mod error;
 pub mod mdbx;
 pub mod al;
+pub mod system;
+pub mod tx;
+pub mod address_index;
+pub mod account_history;
+pub mod signatures;
+pub mod selectors;
+pub mod abi;
+pub mod events;
+pub mod trace;
+pub mod logs;
+pub mod tokens;
+pub mod nft;
+pub mod dex;
+pub mod prices;
 pub mod receipts;
 pub mod tranactions;
 pub mod cube_med;
 pub mod cube_example;
 pub mod cube;
 pub mod scan_ct;
+pub mod parallel;
+pub mod checkpoint;
+pub mod info;
+pub mod output;
 pub mod evm_ct;
+#[cfg(test)]
+mod test_support;
 /// 初始化 tracing（可传入日志级别；否则读取环境变量，默认 info）
 pub fn init_tracing(level: Option<&str>) {
+	use std::io::IsTerminal;
 	use tracing_subscriber::{EnvFilter, fmt, prelude::*};
 
 	let env_filter = match level {
@@ -21,7 +44,12 @@ pub fn init_tracing(level: Option<&str>) {
 		None => EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
 	};
 
-	let fmt_layer = fmt::layer().with_target(true).with_ansi(true);
+	// 日志写 stderr，stdout 只留给记录输出（json/ndjson/csv 可直接被管道解析）；
+	// stderr 不是终端时关闭 ANSI 颜色
+	let fmt_layer = fmt::layer()
+		.with_target(true)
+		.with_writer(std::io::stderr)
+		.with_ansi(std::io::stderr().is_terminal());
 
 	tracing_subscriber::registry()
 		.with(env_filter)
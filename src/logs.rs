@@ -0,0 +1,253 @@
+use std::ops::Range;
+
+use alloy_primitives::{Address, Bloom, BloomInput, Bytes, B256};
+use reth_ethereum_primitives::Receipt;
+use reth_provider::{BlockBodyIndicesProvider, HeaderProvider, ReceiptProvider, TransactionsProvider};
+use serde::Serialize;
+
+use crate::abi::{AbiRegistry, DecodedEvent};
+use crate::checkpoint::Checkpointer;
+use crate::databases::{BscDatabase, BscProvider};
+use crate::error::{AppError, Result};
+use crate::events::{log_text, EventRegistry};
+use crate::info::DataKind;
+use crate::output::Record;
+use crate::parallel::{ParallelScan, RangeScanner};
+
+/// eth_getLogs 风格的过滤条件：地址为 OR 集合；topics 按位置匹配，每个位置为 OR 集合，空集合为通配
+#[derive(Debug, Clone, Default)]
+pub struct LogFilter {
+    pub addresses: Vec<Address>,
+    pub topics: [Vec<B256>; 4],
+}
+
+impl LogFilter {
+    /// 区块 logs bloom 可能包含匹配日志（false 表示可以跳过该区块）
+    pub fn matches_bloom(&self, bloom: &Bloom) -> bool {
+        let any = |items: &mut dyn Iterator<Item = &[u8]>| {
+            let mut items = items.peekable();
+            items.peek().is_none() || items.any(|raw| bloom.contains_input(BloomInput::Raw(raw)))
+        };
+        any(&mut self.addresses.iter().map(|a| a.as_slice()))
+            && self.topics.iter().all(|set| any(&mut set.iter().map(|t| t.as_slice())))
+    }
+
+    /// 精确匹配单条日志
+    pub fn matches(&self, address: Address, topics: &[B256]) -> bool {
+        if !self.addresses.is_empty() && !self.addresses.contains(&address) {
+            return false;
+        }
+        self.topics.iter().enumerate().all(|(i, set)| {
+            set.is_empty() || topics.get(i).is_some_and(|t| set.contains(t))
+        })
+    }
+
+    /// 供检查点配置哈希使用的稳定描述
+    pub fn describe(&self) -> String {
+        let mut s = format!("addresses={:?}", self.addresses);
+        for (i, set) in self.topics.iter().enumerate() {
+            s += &format!(";topic{i}={set:?}");
+        }
+        s
+    }
+}
+
+/// 一条匹配的日志；索引语义与 eth_getLogs 一致（log_index 为区块内序号，removed 恒为 false）
+#[derive(Debug, Clone, Serialize)]
+pub struct LogRow {
+    pub address: Address,
+    pub topics: Vec<B256>,
+    pub data: Bytes,
+    pub block_number: u64,
+    pub block_hash: B256,
+    pub block_timestamp: u64,
+    pub transaction_hash: B256,
+    pub transaction_index: u64,
+    pub log_index: u64,
+    pub removed: bool,
+    /// 事件签名（来自合约 ABI 或事件签名库）
+    pub event: Option<String>,
+    /// 按合约 ABI 或事件签名库解码的事件
+    pub decoded: Option<DecodedEvent>,
+}
+
+impl LogRow {
+    /// 标注并解码事件：先按 ABI 注册表（仅按地址匹配），再按事件签名库
+    pub fn decode_with(&mut self, abis: Option<&AbiRegistry>, events: &EventRegistry) {
+        let abi = abis.and_then(|abis| abis.find(self.address, || None));
+        (self.event, self.decoded) = events.describe_log(abi, &self.topics, &self.data);
+    }
+}
+
+impl Record for LogRow {
+    fn text(&self) -> String {
+        let body = log_text(self.decoded.as_ref(), self.event.as_deref(), &self.topics, &self.data);
+        format!(
+            "block={} tx={:#x} tx_index={} log_index={} {:#x} {body}",
+            self.block_number, self.transaction_hash, self.transaction_index, self.log_index, self.address,
+        )
+    }
+}
+
+/// 逐块用 logs bloom 预筛，只为可能匹配的区块读取回执与交易哈希
+pub struct LogScanner {
+    pub filter: LogFilter,
+}
+
+impl RangeScanner for LogScanner {
+    type Output = LogRow;
+
+    fn scan_chunk(&self, provider: BscProvider, range: Range<u64>) -> Result<Vec<LogRow>> {
+        let mut rows = Vec::new();
+        let mut skipped = 0u64;
+        for header in provider.sealed_headers_range(range.clone())? {
+            if !self.filter.matches_bloom(&header.logs_bloom) {
+                skipped += 1;
+                continue;
+            }
+            let number = header.number;
+            let body = provider
+                .block_body_indices(number)?
+                .ok_or_else(|| AppError::NotFound(format!("block body {number}")))?;
+            if body.tx_count() == 0 {
+                continue;
+            }
+            let receipts: Vec<Receipt> = provider.receipts_by_tx_range(body.tx_num_range())?;
+            if receipts.len() as u64 != body.tx_count() {
+                return Err(AppError::Pruned { kind: DataKind::Receipts, block: number });
+            }
+
+            let mut log_index = 0u64;
+            let mut tx_hashes: Option<Vec<B256>> = None;
+            for (tx_index, receipt) in receipts.iter().enumerate() {
+                for log in &receipt.logs {
+                    if self.filter.matches(log.address, log.data.topics()) {
+                        // bloom 误报的区块不读交易；确有匹配时才一次性读取整块交易哈希
+                        if tx_hashes.is_none() {
+                            let txs = provider.transactions_by_tx_range(body.tx_num_range())?;
+                            if txs.len() as u64 != body.tx_count() {
+                                return Err(AppError::Pruned { kind: DataKind::Bodies, block: number });
+                            }
+                            tx_hashes = Some(txs.iter().map(|tx| *tx.hash()).collect());
+                        }
+                        rows.push(LogRow {
+                            address: log.address,
+                            topics: log.data.topics().to_vec(),
+                            data: log.data.data.clone(),
+                            block_number: number,
+                            block_hash: header.hash(),
+                            block_timestamp: header.timestamp,
+                            transaction_hash: tx_hashes.as_ref().map(|h| h[tx_index]).unwrap_or_default(),
+                            transaction_index: tx_index as u64,
+                            log_index,
+                            removed: false,
+                            event: None,
+                            decoded: None,
+                        });
+                    }
+                    log_index += 1;
+                }
+            }
+        }
+        tracing::debug!(start = range.start, end = range.end, skipped, matched = rows.len(), "Log chunk scanned");
+        Ok(rows)
+    }
+}
+
+/// 流式查询日志，按 (区块, log_index) 顺序回调
+pub fn scan_logs(
+    db: &BscDatabase,
+    range: Range<u64>,
+    filter: LogFilter,
+    engine: &ParallelScan,
+    checkpointer: Option<&Checkpointer>,
+    resume: bool,
+    mut on_log: impl FnMut(LogRow) -> Result<()>,
+) -> Result<()> {
+    if range.is_empty() {
+        return Ok(());
+    }
+    db.ensure_available(DataKind::Receipts, range.start)?;
+    let scanner = LogScanner { filter };
+    engine.run_checkpointed(db, range, &scanner, checkpointer, resume, |_, rows| {
+        rows.into_iter().try_for_each(&mut on_log)
+    })
+}
+
+/// eth_getLogs 等价接口：返回 `range`（左闭右开）内全部匹配日志
+pub fn get_logs(db: &BscDatabase, range: Range<u64>, filter: LogFilter) -> Result<Vec<LogRow>> {
+    ParallelScan::default().collect_with(|engine, push| scan_logs(db, range, filter, engine, None, false, push))
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::keccak256;
+
+    use super::*;
+    use crate::test_support::{BUSD, ROUTER, V2_PAIR, WBNB};
+
+    fn filter(addresses: Vec<Address>, topics: [Vec<B256>; 4]) -> LogFilter {
+        LogFilter { addresses, topics }
+    }
+
+    fn transfer() -> B256 {
+        keccak256("Transfer(address,address,uint256)")
+    }
+
+    fn approval() -> B256 {
+        keccak256("Approval(address,address,uint256)")
+    }
+
+    #[test]
+    fn matches_addresses_and_positional_topics() {
+        let topics = [transfer(), V2_PAIR.into_word(), ROUTER.into_word()];
+
+        assert!(LogFilter::default().matches(BUSD, &topics));
+        assert!(filter(vec![WBNB, BUSD], Default::default()).matches(BUSD, &topics));
+        assert!(!filter(vec![WBNB], Default::default()).matches(BUSD, &topics));
+
+        // 每个位置为 OR 集合，空集合为通配
+        let by_from = filter(vec![], [vec![transfer(), approval()], vec![V2_PAIR.into_word()], vec![], vec![]]);
+        assert!(by_from.matches(BUSD, &topics));
+        let by_to = filter(vec![], [vec![], vec![], vec![V2_PAIR.into_word()], vec![]]);
+        assert!(!by_to.matches(BUSD, &topics));
+    }
+
+    #[test]
+    fn missing_topic_positions_never_match() {
+        // BEP-20 Transfer 没有 topic3，要求 topic3 的过滤条件（如按 ERC-721 tokenId）不匹配
+        let topics = [transfer(), V2_PAIR.into_word(), ROUTER.into_word()];
+        let by_token_id = filter(vec![], [vec![transfer()], vec![], vec![], vec![B256::with_last_byte(42)]]);
+        assert!(!by_token_id.matches(BUSD, &topics));
+
+        // 匿名事件没有 topic0：只有 topics 全部通配时匹配
+        assert!(filter(vec![BUSD], Default::default()).matches(BUSD, &[]));
+        assert!(!filter(vec![], [vec![transfer()], vec![], vec![], vec![]]).matches(BUSD, &[]));
+    }
+
+    #[test]
+    fn bloom_never_rejects_a_matching_block() {
+        let mut bloom = Bloom::default();
+        bloom.accrue(BloomInput::Raw(BUSD.as_slice()));
+        for topic in [transfer(), V2_PAIR.into_word(), ROUTER.into_word()] {
+            bloom.accrue(BloomInput::Raw(topic.as_slice()));
+        }
+
+        assert!(LogFilter::default().matches_bloom(&bloom));
+        let exact = filter(vec![WBNB, BUSD], [vec![transfer()], vec![], vec![ROUTER.into_word()], vec![]]);
+        assert!(exact.matches_bloom(&bloom));
+
+        // 空 bloom 只被全通配的过滤条件接受
+        assert!(LogFilter::default().matches_bloom(&Bloom::default()));
+        assert!(!filter(vec![BUSD], Default::default()).matches_bloom(&Bloom::default()));
+        assert!(!filter(vec![], [vec![approval()], vec![], vec![], vec![]]).matches_bloom(&Bloom::default()));
+    }
+
+    #[test]
+    fn describe_changes_with_filter() {
+        let a = filter(vec![BUSD], [vec![transfer()], vec![], vec![], vec![]]);
+        let b = filter(vec![BUSD], [vec![], vec![transfer()], vec![], vec![]]);
+        assert_ne!(a.describe(), b.describe());
+        assert_eq!(a.describe(), a.clone().describe());
+    }
+}
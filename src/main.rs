@@ -1,11 +1,23 @@
-use bsc_scan::{cli::{Cli, Commands}, databases::BscDatabase};
+use bsc_scan::{
+    address_index::{self, AddressIndex, AddressRole},
+    cli::{AnalyzeView, Cli, Commands, ReceiptStatsView},
+    databases::BscDatabase,
+    dex,
+    error::{AppError, Result},
+    logs::{self, LogFilter},
+    nft,
+    output::{HeaderRow, Output, OutputFormat, TosRow},
+    prices,
+    selectors::{selector_stats, SelectorStatsOptions},
+    signatures::SignatureDb,
+    tokens,
+};
 use clap::Parser;
-use eyre::Result;
 
 fn main() {
     if let Err(e) = run() {
         eprintln!("Error: {:#}", e);
-        std::process::exit(1);
+        std::process::exit(e.exit_code());
     }
 }
 
@@ -14,60 +26,215 @@ fn run() -> Result<()> {
 
     bsc_scan::init_tracing(cli.log.as_deref());
 
-    tracing::info!(version = %env!("CARGO_PKG_VERSION"), "Starting bsc_scan");
+    tracing::info!(version = %env!("CARGO_PKG_VERSION"), chain = %cli.chain, "Starting bsc_scan");
 
-    match cli.command {
+    let open = |db_path: &str| BscDatabase::open(cli.db_config(db_path));
+    let mut out = Output::stdout(cli.format);
+
+    match &cli.command {
+        Commands::Info { db_path } => {
+            let db = open(db_path)?;
+            out.emit(&db.availability()?)?;
+        }
         Commands::Header { db_path, block } => {
-            let db = BscDatabase::new(db_path)?;
-            match db.query_headers_with_blocknumber(block)? {
-                Some(data) => {
-                    println!(
-                        "Block #{:?}\nHash: {:?}\nParent: {:?}\nTimestamp: {}\nTx count: {}",
-                        data.header.number,
-                        data.header.hash_slow(),
-                        data.header.parent_hash,
-                        data.header.timestamp,
-                        data.tx_count,
-                    );
-                }
-                None => {
-                    println!("Block #{} not found or not available", block);
-                }
-            }
+            let db = open(db_path)?;
+            let data = db
+                .query_headers_with_blocknumber(*block)?
+                .ok_or_else(|| AppError::NotFound(format!("block {block}")))?;
+            out.emit(&HeaderRow::from(&data))?;
         }
-        Commands::Tos { db_path, block } => {
-            let db = BscDatabase::new(db_path)?;
-            let tos = bsc_scan::al::analyze_block_transactions_with_to(&db, block)?;
-            for (i, to) in tos.iter().enumerate() {
-                match to {
-                    Some(addr) => println!("{}: {:#x}", i, addr),
-                    None => println!("{}: None", i),
-                }
+        Commands::Tos { db_path, block, exclude_system } => {
+            let db = open(db_path)?;
+            let tos = if *exclude_system {
+                bsc_scan::al::user_transactions_with_to(&db, *block)?
+            } else {
+                bsc_scan::al::analyze_block_transactions_with_to(&db, *block)?.into_iter().enumerate().collect()
+            };
+            for (index, to) in tos {
+                out.emit(&TosRow { index, to })?;
             }
         }
-        Commands::ReceiptsTest { static_dir, block } => {
-            bsc_scan::receipts::test_receipts(static_dir, block)?;
+        Commands::ReceiptsTest { db_path, static_dir, block } => {
+            let db = BscDatabase::open(cli.db_config_with_static_dir(db_path.as_deref(), static_dir.as_deref()))?;
+            out.emit_all(&bsc_scan::receipts::test_receipts(&db, *block)?)?;
+        }
+        Commands::ReceiptStats { db_path, scan, view } => {
+            let db = open(db_path)?;
+            let ckpt = scan.checkpointer(&db, "receipt-stats", "");
+            let ranges = bsc_scan::receipts::receipt_stats(
+                &db,
+                scan.range(&db),
+                &scan.engine(),
+                ckpt.as_ref(),
+                scan.resume,
+                |block| match view {
+                    ReceiptStatsView::Blocks => out.emit(block),
+                    ReceiptStatsView::Ranges => Ok(()),
+                },
+            )?;
+            if *view == ReceiptStatsView::Ranges {
+                out.emit_all(&ranges)?;
+            }
         }
-        Commands::TransactionsTest { static_dir, block } => {
-            bsc_scan::tranactions::test_transactions(static_dir, block)?;
+        Commands::TransactionsTest { db_path, static_dir, block } => {
+            let db = BscDatabase::open(cli.db_config_with_static_dir(db_path.as_deref(), static_dir.as_deref()))?;
+            out.emit_all(&bsc_scan::tranactions::test_transactions(&db, *block)?)?;
         }
         Commands::CubeDemo { db_path, block, tx } => {
+            let db = open(db_path)?;
             let tx_hash_opt = tx
                 .as_deref()
                 .and_then(|s| s.parse().ok());
-            bsc_scan::cube_med::demo_common(db_path, block, tx_hash_opt)?;
+            bsc_scan::cube_med::demo_common(&db, *block, tx_hash_opt)?;
+        }
+        Commands::ScanCt { db_path, scan } => {
+            let db = open(db_path)?;
+            let ckpt = scan.checkpointer(&db, "scan_ct", "");
+            bsc_scan::scan_ct::scan_contract_creations(
+                &db,
+                scan.range(&db),
+                &scan.engine(),
+                ckpt.as_ref(),
+                scan.resume,
+                |c| out.emit(&c),
+            )?;
+        }
+        Commands::Analyze { db_path, scan, view, exclude_system } => {
+            if *view == AnalyzeView::All && cli.format == OutputFormat::Csv {
+                return Err(AppError::InvalidArg(
+                    "--view all mixes record types; use --view blocks or --view txs with csv".to_string(),
+                ));
+            }
+            let db = open(db_path)?;
+            let extra = format!("view={view:?};exclude_system={exclude_system}");
+            let ckpt = scan.checkpointer(&db, "analyze", &extra);
+            bsc_scan::al::analyze_range(
+                &db,
+                scan.range(&db),
+                &scan.engine(),
+                *exclude_system,
+                ckpt.as_ref(),
+                scan.resume,
+                |block| {
+                    if *view != AnalyzeView::Txs {
+                        out.emit(&block.stats)?;
+                    }
+                    if *view != AnalyzeView::Blocks {
+                        out.emit_all(&block.txs)?;
+                    }
+                    Ok(())
+                },
+            )?;
+        }
+        Commands::Tx { db_path, hash } => {
+            let db = open(db_path)?;
+            let abis = cli.abi_registry()?;
+            let events = cli.event_registry()?;
+            out.emit(&bsc_scan::tx::tx_details(&db, *hash, abis.as_ref(), &events)?)?;
+        }
+        Commands::Trace { db_path, hash } => {
+            let db = open(db_path)?;
+            let abis = cli.abi_registry()?;
+            let events = cli.event_registry()?;
+            out.emit_all(&bsc_scan::trace::trace_tx(&db, *hash, abis.as_ref(), &events)?)?;
         }
-        Commands::ScanCt { db_path } => {
-            bsc_scan::scan_ct::scan_contract_creations(db_path)?;
+        Commands::Logs { db_path, scan, address, topic0, topic1, topic2, topic3 } => {
+            let db = open(db_path)?;
+            let abis = cli.abi_registry()?;
+            let events = cli.event_registry()?;
+            let filter = LogFilter {
+                addresses: address.clone(),
+                topics: [topic0.clone(), topic1.clone(), topic2.clone(), topic3.clone()],
+            };
+            let ckpt = scan.checkpointer(&db, "logs", &filter.describe());
+            logs::scan_logs(&db, scan.range(&db), filter, &scan.engine(), ckpt.as_ref(), scan.resume, |mut row| {
+                row.decode_with(abis.as_ref(), &events);
+                out.emit(&row)
+            })?;
+        }
+        Commands::TokenTransfers { db_path, scan, token } => {
+            let db = open(db_path)?;
+            let ckpt = scan.checkpointer(&db, "token-transfers", &format!("tokens={token:?}"));
+            tokens::scan_token_transfers(
+                &db,
+                scan.range(&db),
+                token.clone(),
+                &scan.engine(),
+                ckpt.as_ref(),
+                scan.resume,
+                |t| out.emit(&t),
+            )?;
+        }
+        Commands::NftTransfers { db_path, scan, token } => {
+            let db = open(db_path)?;
+            let ckpt = scan.checkpointer(&db, "nft-transfers", &format!("tokens={token:?}"));
+            nft::scan_nft_transfers(
+                &db,
+                scan.range(&db),
+                token.clone(),
+                &scan.engine(),
+                ckpt.as_ref(),
+                scan.resume,
+                |t| out.emit(&t),
+            )?;
+        }
+        Commands::Swaps { db_path, scan, pools } => {
+            let db = open(db_path)?;
+            let ckpt = scan.checkpointer(&db, "swaps", &pools.describe());
+            dex::scan_swaps(&db, scan.range(&db), pools.filter(), &scan.engine(), ckpt.as_ref(), scan.resume, |row| {
+                out.emit(&row)
+            })?;
+        }
+        Commands::Prices { db_path, scan, pools } => {
+            let db = open(db_path)?;
+            let ckpt = scan.checkpointer(&db, "prices", &pools.describe());
+            prices::scan_prices(&db, scan.range(&db), pools.filter(), &scan.engine(), ckpt.as_ref(), scan.resume, |p| {
+                out.emit(&p)
+            })?;
+        }
+        Commands::AddressIndex { db_path, index_dir, blocks } => {
+            let db = open(db_path)?;
+            let mut index = AddressIndex::open(index_dir, db.chain_spec.chain.id())?;
+            let update = index.update(&db, blocks.from, blocks.to.unwrap_or(db.latest_block), &blocks.engine())?;
+            out.emit(&update)?;
+        }
+        Commands::AddressTxs { db_path, index_dir, address, role, after, limit } => {
+            let db = open(db_path)?;
+            let index = AddressIndex::open(index_dir, db.chain_spec.chain.id())?;
+            let entries = index.query(*address, AddressRole::mask(role), *after, *limit)?;
+            if let Some(last) = entries.last().filter(|_| entries.len() == *limit) {
+                tracing::info!(next_after = last.tx_num, "More results may follow; pass --after to continue");
+            }
+            out.emit_all(&address_index::resolve_entries(&db, &entries)?)?;
+        }
+        Commands::AccountHistory { db_path, address, from, to } => {
+            let db = open(db_path)?;
+            let range = *from..=to.unwrap_or(db.latest_block);
+            out.emit_all(&bsc_scan::account_history::account_history(&db, *address, range)?)?;
+        }
+        Commands::Selectors { db_path, blocks, by_contract, contract, signatures, top, exclude_system } => {
+            let mut sigs = SignatureDb::bundled();
+            for file in signatures {
+                sigs.import(file)?;
+            }
+            let db = open(db_path)?;
+            let options = SelectorStatsOptions {
+                by_contract: *by_contract,
+                contracts: contract.clone(),
+                exclude_system: *exclude_system,
+            };
+            let stats = selector_stats(&db, blocks.range(&db), &blocks.engine(), options, &sigs, *top)?;
+            out.emit_all(&stats)?;
         }
-        Commands::ScanCtBlock { db_path, block } => {
-            let db = BscDatabase::new(db_path)?;
-            bsc_scan::scan_ct::print_block_contract_creations(&db, block)?;
+        Commands::ScanCtBlock { db_path, block, exclude_system } => {
+            let db = open(db_path)?;
+            out.emit_all(&bsc_scan::scan_ct::block_contract_creation_rows(&db, *block, *exclude_system)?)?;
         }
         Commands::EvmCtTest { db_path, block } => {
-            bsc_scan::evm_ct::evm_ct_test(block, db_path)?;
+            let db = open(db_path)?;
+            out.emit_all(&bsc_scan::evm_ct::evm_ct_test(&db, *block)?)?;
         }
     }
 
-    Ok(())
+    out.finish()
 }
@@ -1,23 +1,19 @@
-use std::path::Path;
-use std::sync::Arc;
 use reth_db::{
-    open_db_read_only,
-    mdbx::DatabaseArguments,
     tables,                // access table definitions like PlainAccountState
     cursor::DbCursorRO,
     transaction::DbTx,     // RO transaction trait
     Database,              // brings tx() into scope for DatabaseEnv
 };
 
-pub fn mdbxinit() -> eyre::Result<()> {
-    // 1. 打开 MDBX 数据库目录（注意传入的是目录，而非 mdbx.dat 文件本身）
-    let db = Arc::new(open_db_read_only(
-        Path::new("/ethereumdata/bsc/db"),
-        DatabaseArguments::default(),
-    )?);
+use crate::databases::BscDatabase;
+use crate::error::Result;
+
+pub fn mdbxinit(db: &BscDatabase) -> Result<()> {
+    // 1. 复用 BscDatabase 已打开的 MDBX 环境（目录由 BscDbConfig 决定）
+    let env = db.mdbx();
 
     // 2. 启动只读事务
-    let tx = db.tx()?;
+    let tx = env.tx()?;
 
     // 3. 读取表：以账户表为例（PlainAccountState）
     let mut cursor = tx.cursor_read::<tables::PlainAccountState>()?;
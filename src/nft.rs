@@ -0,0 +1,240 @@
+use std::ops::Range;
+
+use alloy_dyn_abi::{DynSolType, DynSolValue};
+use alloy_primitives::{b256, Address, B256, U256};
+use serde::Serialize;
+
+use crate::checkpoint::Checkpointer;
+use crate::databases::BscDatabase;
+use crate::error::Result;
+use crate::logs::{scan_logs, LogFilter, LogRow};
+use crate::output::Record;
+use crate::parallel::ParallelScan;
+use crate::tokens::{topic_address, TRANSFER_TOPIC};
+
+/// keccak256("TransferSingle(address,address,address,uint256,uint256)")
+pub const TRANSFER_SINGLE_TOPIC: B256 = b256!("c3d58168c5ae7397731d063d5bbf3d657854427343f4c083240f7aacaa2d0f62");
+/// keccak256("TransferBatch(address,address,address,uint256[],uint256[])")
+pub const TRANSFER_BATCH_TOPIC: B256 = b256!("4a39dc06d4c0dbc64b70af90fd698a233a518aa5d07e595d983b8c0526c8f7fb");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NftStandard {
+    Erc721,
+    Erc1155,
+}
+
+/// 一笔 NFT 转账；ERC-1155 TransferBatch 按 (id, amount) 展开为多行
+#[derive(Debug, Clone, Serialize)]
+pub struct NftTransfer {
+    pub block_number: u64,
+    pub transaction_hash: B256,
+    pub log_index: u64,
+    pub standard: NftStandard,
+    pub token: Address,
+    /// ERC-1155 的 operator（ERC-721 为 None）
+    pub operator: Option<Address>,
+    pub from: Address,
+    pub to: Address,
+    pub token_id: U256,
+    /// 转移数量（ERC-721 恒为 1）
+    pub amount: U256,
+    /// 在 TransferBatch 中的位置（其他事件为 None）
+    pub batch_index: Option<usize>,
+}
+
+impl Record for NftTransfer {
+    fn text(&self) -> String {
+        let batch = self.batch_index.map(|i| format!(" batch[{i}]")).unwrap_or_default();
+        format!(
+            "block={} tx={:#x} log_index={}{batch} {:?} token={:#x} {:#x} -> {:#x} id={} amount={}",
+            self.block_number,
+            self.transaction_hash,
+            self.log_index,
+            self.standard,
+            self.token,
+            self.from,
+            self.to,
+            self.token_id,
+            self.amount,
+        )
+    }
+}
+
+/// 解码一条 ERC-721 Transfer / ERC-1155 TransferSingle / TransferBatch 日志；形状不符时返回空
+pub fn nft_transfers_from_log(log: &LogRow) -> Vec<NftTransfer> {
+    // ERC-721: Transfer(from, to, tokenId)；ERC-1155: TransferSingle/Batch(operator, from, to, ...)，from/to 均在 topic1/2 之后
+    let row = |standard, operator: Option<Address>, token_id, amount, batch_index| NftTransfer {
+        block_number: log.block_number,
+        transaction_hash: log.transaction_hash,
+        log_index: log.log_index,
+        standard,
+        token: log.address,
+        operator,
+        from: topic_address(&log.topics[1 + usize::from(operator.is_some())]),
+        to: topic_address(&log.topics[2 + usize::from(operator.is_some())]),
+        token_id,
+        amount,
+        batch_index,
+    };
+    match (log.topics.first(), log.topics.len()) {
+        // ERC-721：tokenId indexed，data 为空（3 个 topic 的是 BEP-20）
+        (Some(t), 4) if *t == TRANSFER_TOPIC && log.data.is_empty() => {
+            let token_id = U256::from_be_bytes(log.topics[3].0);
+            vec![row(NftStandard::Erc721, None, token_id, U256::from(1), None)]
+        }
+        (Some(t), 4) if *t == TRANSFER_SINGLE_TOPIC && log.data.len() == 64 => {
+            let operator = Some(topic_address(&log.topics[1]));
+            let id = U256::from_be_slice(&log.data[..32]);
+            let amount = U256::from_be_slice(&log.data[32..]);
+            vec![row(NftStandard::Erc1155, operator, id, amount, None)]
+        }
+        (Some(t), 4) if *t == TRANSFER_BATCH_TOPIC => {
+            let operator = Some(topic_address(&log.topics[1]));
+            let Some((ids, amounts)) = decode_batch(&log.data) else {
+                tracing::debug!(block = log.block_number, log_index = log.log_index, "Malformed TransferBatch");
+                return Vec::new();
+            };
+            ids.into_iter()
+                .zip(amounts)
+                .enumerate()
+                .map(|(i, (id, amount))| row(NftStandard::Erc1155, operator, id, amount, Some(i)))
+                .collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// 解码 TransferBatch 的 (uint256[] ids, uint256[] values)；长度不一致视为畸形
+fn decode_batch(data: &[u8]) -> Option<(Vec<U256>, Vec<U256>)> {
+    let ty = DynSolType::Tuple(vec![
+        DynSolType::Array(Box::new(DynSolType::Uint(256))),
+        DynSolType::Array(Box::new(DynSolType::Uint(256))),
+    ]);
+    let value = ty.abi_decode_params(data).ok()?;
+    let [ids, amounts] = value.as_tuple()? else { return None };
+    let uints = |v: &DynSolValue| -> Option<Vec<U256>> {
+        v.as_array()?.iter().map(|x| x.as_uint().map(|(u, _)| u)).collect()
+    };
+    let (ids, amounts) = (uints(ids)?, uints(amounts)?);
+    (ids.len() == amounts.len()).then_some((ids, amounts))
+}
+
+/// 扫描区块范围内的 ERC-721 / ERC-1155 转账，按 (区块, log_index, batch_index) 顺序回调；
+/// `tokens` 为空表示全部合约
+pub fn scan_nft_transfers(
+    db: &BscDatabase,
+    range: Range<u64>,
+    tokens: Vec<Address>,
+    engine: &ParallelScan,
+    checkpointer: Option<&Checkpointer>,
+    resume: bool,
+    mut on_transfer: impl FnMut(NftTransfer) -> Result<()>,
+) -> Result<()> {
+    let topic0 = vec![TRANSFER_TOPIC, TRANSFER_SINGLE_TOPIC, TRANSFER_BATCH_TOPIC];
+    let filter = LogFilter { addresses: tokens, topics: [topic0, vec![], vec![], vec![]] };
+    scan_logs(db, range, filter, engine, checkpointer, resume, |log| {
+        nft_transfers_from_log(&log).into_iter().try_for_each(&mut on_transfer)
+    })
+}
+
+/// 返回 `range`（左闭右开）内全部 NFT 转账
+pub fn nft_transfers(db: &BscDatabase, range: Range<u64>, tokens: Vec<Address>) -> Result<Vec<NftTransfer>> {
+    ParallelScan::default()
+        .collect_with(|engine, push| scan_nft_transfers(db, range, tokens, engine, None, false, push))
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::{address, keccak256};
+
+    use super::*;
+    use crate::test_support::{log, words, ROUTER, V2_PAIR, WBNB};
+
+    /// Pancake Squad（ERC-721）
+    const SQUAD: Address = address!("0a8901b0E25DEb55A87524f0cC164E9644020EBA");
+
+    fn erc1155_topics(topic0: B256) -> [B256; 4] {
+        [topic0, ROUTER.into_word(), V2_PAIR.into_word(), WBNB.into_word()]
+    }
+
+    /// TransferBatch 的 data：两个动态数组的偏移、长度与元素
+    fn batch_data(ids: &[u64], values: &[u64]) -> Vec<u8> {
+        let head = 64 + 32 * (1 + ids.len() as u64);
+        let mut fields = vec![U256::from(64), U256::from(head), U256::from(ids.len())];
+        fields.extend(ids.iter().map(|&id| U256::from(id)));
+        fields.push(U256::from(values.len()));
+        fields.extend(values.iter().map(|&v| U256::from(v)));
+        words(&fields)
+    }
+
+    #[test]
+    fn event_topics_are_event_hashes() {
+        let single = keccak256("TransferSingle(address,address,address,uint256,uint256)");
+        assert_eq!(TRANSFER_SINGLE_TOPIC, single);
+        assert_eq!(TRANSFER_BATCH_TOPIC, keccak256("TransferBatch(address,address,address,uint256[],uint256[])"));
+    }
+
+    #[test]
+    fn decodes_erc721_transfer() {
+        let topics = [TRANSFER_TOPIC, V2_PAIR.into_word(), ROUTER.into_word(), B256::from(U256::from(1234))];
+        let rows = nft_transfers_from_log(&log(SQUAD, &topics, &[]));
+        assert_eq!(rows.len(), 1);
+        let row = &rows[0];
+        assert_eq!((row.standard, row.token, row.operator), (NftStandard::Erc721, SQUAD, None));
+        assert_eq!((row.from, row.to), (V2_PAIR, ROUTER));
+        assert_eq!((row.token_id, row.amount, row.batch_index), (U256::from(1234), U256::from(1), None));
+    }
+
+    #[test]
+    fn bep20_shaped_transfer_is_not_an_nft() {
+        // 3 个 topic + 32 字节 amount 是 BEP-20
+        let erc20 = [TRANSFER_TOPIC, V2_PAIR.into_word(), ROUTER.into_word()];
+        assert!(nft_transfers_from_log(&log(SQUAD, &erc20, &words(&[U256::from(1)]))).is_empty());
+        // 4 个 topic 但 data 非空：形状不符，不猜测
+        let topics = [TRANSFER_TOPIC, V2_PAIR.into_word(), ROUTER.into_word(), B256::with_last_byte(1)];
+        assert!(nft_transfers_from_log(&log(SQUAD, &topics, &words(&[U256::from(1)]))).is_empty());
+    }
+
+    #[test]
+    fn decodes_transfer_single_and_rejects_bad_shapes() {
+        let data = words(&[U256::from(5), U256::from(3)]);
+        let rows = nft_transfers_from_log(&log(SQUAD, &erc1155_topics(TRANSFER_SINGLE_TOPIC), &data));
+        assert_eq!(rows.len(), 1);
+        assert_eq!((rows[0].standard, rows[0].operator), (NftStandard::Erc1155, Some(ROUTER)));
+        assert_eq!((rows[0].from, rows[0].to), (V2_PAIR, WBNB));
+        assert_eq!((rows[0].token_id, rows[0].amount), (U256::from(5), U256::from(3)));
+
+        // operator 未 indexed（3 个 topic）或 data 不是 (id, value)
+        let three = &erc1155_topics(TRANSFER_SINGLE_TOPIC)[..3];
+        assert!(nft_transfers_from_log(&log(SQUAD, three, &data)).is_empty());
+        let long = words(&[U256::from(5), U256::from(3), U256::ZERO]);
+        assert!(nft_transfers_from_log(&log(SQUAD, &erc1155_topics(TRANSFER_SINGLE_TOPIC), &long)).is_empty());
+    }
+
+    #[test]
+    fn expands_transfer_batch_in_order() {
+        let data = batch_data(&[1, 7], &[10, 1]);
+        let rows = nft_transfers_from_log(&log(SQUAD, &erc1155_topics(TRANSFER_BATCH_TOPIC), &data));
+        let expanded: Vec<_> = rows.iter().map(|r| (r.batch_index, r.token_id, r.amount)).collect();
+        assert_eq!(expanded, [(Some(0), U256::from(1), U256::from(10)), (Some(1), U256::from(7), U256::from(1))]);
+        assert!(rows.iter().all(|r| r.operator == Some(ROUTER) && r.from == V2_PAIR && r.to == WBNB));
+
+        // 空批次不产生行
+        let empty = batch_data(&[], &[]);
+        assert!(nft_transfers_from_log(&log(SQUAD, &erc1155_topics(TRANSFER_BATCH_TOPIC), &empty)).is_empty());
+    }
+
+    #[test]
+    fn rejects_malformed_batches() {
+        // ids 与 values 长度不一致
+        let mismatched = batch_data(&[1], &[1, 2]);
+        assert!(decode_batch(&mismatched).is_none());
+        assert!(nft_transfers_from_log(&log(SQUAD, &erc1155_topics(TRANSFER_BATCH_TOPIC), &mismatched)).is_empty());
+
+        // 数组偏移越界
+        let out_of_range = words(&[U256::from(64), U256::from(4096), U256::ZERO]);
+        assert!(decode_batch(&out_of_range).is_none());
+        assert!(decode_batch(&[]).is_none());
+    }
+}
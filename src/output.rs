@@ -0,0 +1,223 @@
+use std::io::{self, Stdout, Write};
+
+use alloy_primitives::{Address, B256};
+use clap::ValueEnum;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::databases::BlockData;
+use crate::error::Result;
+
+/// 输出格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// 人类可读文本
+    #[default]
+    Text,
+    /// 单个 JSON 数组
+    Json,
+    /// 每行一个 JSON 对象
+    Ndjson,
+    /// 带表头的 CSV（列顺序即结构体字段顺序，嵌套结构展开为 `a.b` 列）
+    Csv,
+}
+
+/// 可输出的记录：字段定义即 JSON/CSV 的稳定 schema
+pub trait Record: Serialize {
+    /// text 格式下的单行（或多行）表示
+    fn text(&self) -> String;
+}
+
+/// 流式输出器；json 格式在 `finish` 时闭合数组
+pub struct Output<W: Write> {
+    format: OutputFormat,
+    out: W,
+    count: usize,
+    /// CSV 的列，由第一条记录确定
+    columns: Vec<String>,
+}
+
+impl Output<Stdout> {
+    pub fn stdout(format: OutputFormat) -> Self {
+        Self::new(format, io::stdout())
+    }
+}
+
+impl<W: Write> Output<W> {
+    pub fn new(format: OutputFormat, out: W) -> Self {
+        Self { format, out, count: 0, columns: Vec::new() }
+    }
+
+    pub fn format(&self) -> OutputFormat {
+        self.format
+    }
+
+    /// 输出一条记录
+    pub fn emit<T: Record>(&mut self, record: &T) -> Result<()> {
+        match self.format {
+            OutputFormat::Text => writeln!(self.out, "{}", record.text())?,
+            OutputFormat::Ndjson => {
+                serde_json::to_writer(&mut self.out, record).map_err(io::Error::from)?;
+                writeln!(self.out)?;
+            }
+            OutputFormat::Json => {
+                self.out.write_all(if self.count == 0 { b"[\n" } else { b",\n" })?;
+                serde_json::to_writer(&mut self.out, record).map_err(io::Error::from)?;
+            }
+            OutputFormat::Csv => {
+                let Value::Object(fields) = serde_json::to_value(record).map_err(io::Error::from)? else {
+                    return Err(io::Error::other("csv output requires struct records").into());
+                };
+                let mut cells = Vec::new();
+                flatten_fields(fields, "", &self.columns, &mut cells);
+                if self.count == 0 {
+                    self.columns = cells.iter().map(|(k, _)| k.clone()).collect();
+                    let header: Vec<String> = self.columns.iter().map(|k| csv_escape(k)).collect();
+                    writeln!(self.out, "{}", header.join(","))?;
+                }
+                // 列集合以第一条记录为准：缺少的列留空，多出的列报错
+                if let Some((extra, _)) = cells.iter().find(|(k, _)| !self.columns.contains(k)) {
+                    let msg = format!("csv record {} has column {extra:?} missing from the header", self.count);
+                    return Err(io::Error::other(msg).into());
+                }
+                let row: Vec<String> = self
+                    .columns
+                    .iter()
+                    .map(|col| cells.iter().find(|(k, _)| k == col).map(|(_, v)| csv_cell(v)).unwrap_or_default())
+                    .collect();
+                writeln!(self.out, "{}", row.join(","))?;
+            }
+        }
+        self.count += 1;
+        Ok(())
+    }
+
+    /// 输出一组记录
+    pub fn emit_all<'a, T: Record + 'a>(&mut self, records: impl IntoIterator<Item = &'a T>) -> Result<()> {
+        for record in records {
+            self.emit(record)?;
+        }
+        Ok(())
+    }
+
+    /// 已输出的记录数
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// 结束输出（json 闭合数组）并 flush
+    pub fn finish(mut self) -> Result<()> {
+        if self.format == OutputFormat::Json {
+            self.out.write_all(if self.count == 0 { b"[]\n" } else { b"\n]\n" })?;
+        }
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+/// 把嵌套对象展开为 `a.b` 列。表头确定后：表头中已有该字段本身（第一条记录中为 null）时整体作为一列；
+/// 第一条记录中展开过的字段为 null 时其各列留空
+fn flatten_fields(
+    fields: serde_json::Map<String, Value>,
+    prefix: &str,
+    columns: &[String],
+    out: &mut Vec<(String, Value)>,
+) {
+    for (key, value) in fields {
+        let key = format!("{prefix}{key}");
+        match value {
+            Value::Object(nested) if !columns.contains(&key) => {
+                flatten_fields(nested, &format!("{key}."), columns, out)
+            }
+            Value::Null if !columns.is_empty() && !columns.contains(&key) => {}
+            value => out.push((key, value)),
+        }
+    }
+}
+
+fn csv_cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => csv_escape(s),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        other => csv_escape(&other.to_string()),
+    }
+}
+
+fn csv_escape(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// Header 命令输出
+#[derive(Debug, Clone, Serialize)]
+pub struct HeaderRow {
+    pub number: u64,
+    pub hash: B256,
+    pub parent_hash: B256,
+    pub timestamp: u64,
+    pub tx_count: usize,
+    pub gas_used: u64,
+    pub gas_limit: u64,
+    pub base_fee_per_gas: Option<u64>,
+    pub miner: Address,
+}
+
+impl From<&BlockData> for HeaderRow {
+    fn from(data: &BlockData) -> Self {
+        let h = &data.header;
+        Self {
+            number: h.number,
+            hash: h.hash_slow(),
+            parent_hash: h.parent_hash,
+            timestamp: h.timestamp,
+            tx_count: data.tx_count,
+            gas_used: h.gas_used,
+            gas_limit: h.gas_limit,
+            base_fee_per_gas: h.base_fee_per_gas,
+            miner: h.beneficiary,
+        }
+    }
+}
+
+impl Record for HeaderRow {
+    fn text(&self) -> String {
+        format!(
+            "Block #{}\nHash: {:?}\nParent: {:?}\nTimestamp: {}\nTx count: {}",
+            self.number, self.hash, self.parent_hash, self.timestamp, self.tx_count,
+        )
+    }
+}
+
+/// Tos 命令输出：交易序号与 to（合约创建为 None）
+#[derive(Debug, Clone, Serialize)]
+pub struct TosRow {
+    pub index: usize,
+    pub to: Option<Address>,
+}
+
+impl Record for TosRow {
+    fn text(&self) -> String {
+        match self.to {
+            Some(addr) => format!("{}: {:#x}", self.index, addr),
+            None => format!("{}: None", self.index),
+        }
+    }
+}
+
+/// ScanCtBlock 命令输出
+#[derive(Debug, Clone, Serialize)]
+pub struct CreationFlagRow {
+    pub index: usize,
+    pub is_create: bool,
+}
+
+impl Record for CreationFlagRow {
+    fn text(&self) -> String {
+        format!("{}: {}", self.index, if self.is_create { "CREATE" } else { "CALL" })
+    }
+}
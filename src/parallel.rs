@@ -0,0 +1,162 @@
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Condvar, Mutex};
+
+use eyre::eyre;
+
+use crate::databases::{BscDatabase, BscProvider};
+use crate::error::Result;
+
+/// 默认每个分片的区块数
+pub const DEFAULT_CHUNK_SIZE: u64 = 10_000;
+
+/// 每个线程最多领先已输出分片的分片数；限制乱序缓存与结果通道占用的内存
+const AHEAD_PER_THREAD: usize = 4;
+
+/// 可并行执行的区块范围扫描器
+pub trait RangeScanner: Sync {
+    type Output: Send;
+
+    /// 扫描 `range`（左闭右开）；provider 为当前 worker 独占的只读 provider
+    fn scan_chunk(&self, provider: BscProvider, range: Range<u64>) -> Result<Vec<Self::Output>>;
+}
+
+/// 并行扫描引擎：切分区块范围 -> 线程池执行 -> 按区块顺序合并输出
+#[derive(Debug, Clone)]
+pub struct ParallelScan {
+    pub chunk_size: u64,
+    pub threads: usize,
+}
+
+impl Default for ParallelScan {
+    fn default() -> Self {
+        let threads = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+        Self { chunk_size: DEFAULT_CHUNK_SIZE, threads }
+    }
+}
+
+impl ParallelScan {
+    pub fn new(chunk_size: u64, threads: usize) -> Self {
+        Self { chunk_size: chunk_size.max(1), threads: threads.max(1) }
+    }
+
+    /// 将范围切分为分片
+    pub fn chunks(&self, range: Range<u64>) -> Vec<Range<u64>> {
+        let step = self.chunk_size.max(1);
+        let mut chunks = Vec::new();
+        let mut start = range.start;
+        while start < range.end {
+            let end = (start + step).min(range.end);
+            chunks.push(start..end);
+            start = end;
+        }
+        chunks
+    }
+
+    /// 执行扫描。`sink` 按区块顺序接收每个分片的结果，返回错误时中止所有 worker。
+    pub fn run<S, F>(&self, db: &BscDatabase, range: Range<u64>, scanner: &S, mut sink: F) -> Result<()>
+    where
+        S: RangeScanner,
+        F: FnMut(Range<u64>, Vec<S::Output>) -> Result<()>,
+    {
+        let chunks = self.chunks(range);
+        if chunks.is_empty() {
+            return Ok(());
+        }
+        let threads = self.threads.max(1).min(chunks.len());
+        tracing::info!(chunks = chunks.len(), threads, chunk_size = self.chunk_size, "Starting parallel scan");
+
+        let next = AtomicUsize::new(0);
+        let stop = AtomicBool::new(false);
+        // worker 只领取 [emitted, emitted + window) 内的分片，超前时等待合并线程追上
+        let window = threads * AHEAD_PER_THREAD;
+        let progress = (Mutex::new(0usize), Condvar::new());
+        let (tx, rx) = mpsc::sync_channel::<(usize, Result<Vec<S::Output>>)>(window);
+
+        std::thread::scope(|scope| {
+            for _ in 0..threads {
+                let tx = tx.clone();
+                let (next, stop, chunks, progress) = (&next, &stop, &chunks, &progress);
+                scope.spawn(move || {
+                    while !stop.load(Ordering::Relaxed) {
+                        let idx = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(chunk) = chunks.get(idx) else { break };
+                        let (lock, cvar) = progress;
+                        let guard = lock.lock().unwrap_or_else(|e| e.into_inner());
+                        drop(
+                            cvar.wait_while(guard, |emitted| idx >= *emitted + window && !stop.load(Ordering::Relaxed))
+                                .unwrap_or_else(|e| e.into_inner()),
+                        );
+                        if stop.load(Ordering::Relaxed) {
+                            break;
+                        }
+                        let result = db.provider().and_then(|p| scanner.scan_chunk(p, chunk.clone()));
+                        if tx.send((idx, result)).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+            drop(tx);
+
+            // 乱序到达的分片先缓存，凑齐前缀后按序交给 sink
+            let mut pending = BTreeMap::new();
+            let mut emitted = 0usize;
+            let outcome = (|| {
+                for (idx, result) in rx.iter() {
+                    pending.insert(idx, result);
+                    while let Some(result) = pending.remove(&emitted) {
+                        let chunk = chunks[emitted].clone();
+                        let out = result.inspect_err(|e| {
+                            tracing::error!(start = chunk.start, end = chunk.end, error = %e, "Chunk failed");
+                        })?;
+                        tracing::debug!(start = chunk.start, end = chunk.end, items = out.len(), "Chunk completed");
+                        sink(chunk, out)?;
+                        emitted += 1;
+                        let (lock, cvar) = &progress;
+                        *lock.lock().unwrap_or_else(|e| e.into_inner()) = emitted;
+                        cvar.notify_all();
+                    }
+                }
+                if emitted == chunks.len() {
+                    Ok(())
+                } else {
+                    Err(eyre!("parallel scan stopped after {emitted}/{} chunks", chunks.len()).into())
+                }
+            })();
+            if outcome.is_err() {
+                stop.store(true, Ordering::Relaxed);
+                // 唤醒等待窗口的 worker，使其看到 stop 后退出
+                let (lock, cvar) = &progress;
+                drop(lock.lock().unwrap_or_else(|e| e.into_inner()));
+                cvar.notify_all();
+            }
+            drop(rx);
+            outcome
+        })
+    }
+
+    /// 便捷版：收集全部结果（按区块顺序）
+    pub fn collect<S: RangeScanner>(&self, db: &BscDatabase, range: Range<u64>, scanner: &S) -> Result<Vec<S::Output>> {
+        let mut all = Vec::new();
+        self.run(db, range, scanner, |_, out| {
+            all.extend(out);
+            Ok(())
+        })?;
+        Ok(all)
+    }
+
+    /// 便捷版：以本引擎、不带检查点执行回调式扫描（如 `scan_logs`），按回调顺序收集全部输出
+    pub fn collect_with<T>(
+        &self,
+        scan: impl FnOnce(&Self, &mut dyn FnMut(T) -> Result<()>) -> Result<()>,
+    ) -> Result<Vec<T>> {
+        let mut all = Vec::new();
+        scan(self, &mut |item| {
+            all.push(item);
+            Ok(())
+        })?;
+        Ok(all)
+    }
+}
@@ -0,0 +1,283 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ops::Range;
+
+use alloy_primitives::{Address, U256};
+use serde::{Deserialize, Serialize};
+
+use crate::checkpoint::Checkpointer;
+use crate::databases::BscDatabase;
+use crate::dex::{PoolEvent, PoolFilter, PoolInfo, PoolKind, PoolResolver, V2_SYNC_TOPIC, V3_SWAP_TOPIC};
+use crate::error::Result;
+use crate::info::DataKind;
+use crate::logs::{LogFilter, LogRow, LogScanner};
+use crate::output::Record;
+use crate::parallel::ParallelScan;
+
+/// 某池子在某区块结束时的状态与价格。
+///
+/// 序列是稀疏的：只在该区块内有 Sync（V2）或 Swap（V3）时输出，其余区块的状态等于该池子的上一行。
+/// 池子状态跨分片延续，并随检查点保存，恢复扫描后从上次的状态继续。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PricePoint {
+    pub block_number: u64,
+    pub block_timestamp: u64,
+    pub pool: Address,
+    pub kind: PoolKind,
+    pub token0: Address,
+    pub token1: Address,
+    pub fee: u32,
+    /// V2 储备
+    pub reserve0: Option<U256>,
+    pub reserve1: Option<U256>,
+    /// V3 价格、tick 与当前流动性
+    pub sqrt_price_x96: Option<U256>,
+    pub tick: Option<i32>,
+    pub liquidity: Option<u128>,
+    /// 每个 token0 最小单位可换得的 token1 最小单位（V2 储备为 0 时为 None）
+    pub price_raw: Option<f64>,
+    pub decimals0: Option<u8>,
+    pub decimals1: Option<u8>,
+    /// 按 decimals 调整后的价格：1 token0 = price token1
+    pub price: Option<f64>,
+    /// 该区块内的状态更新次数
+    pub updates: u32,
+}
+
+impl Record for PricePoint {
+    fn text(&self) -> String {
+        let price = match (self.price, self.price_raw) {
+            (Some(p), _) => format!("price={p}"),
+            (None, Some(raw)) => format!("price_raw={raw}"),
+            (None, None) => "price=?".to_string(),
+        };
+        format!(
+            "block={} {:?} pool={:#x} {:#x}/{:#x} {price} updates={}",
+            self.block_number, self.kind, self.pool, self.token1, self.token0, self.updates,
+        )
+    }
+}
+
+impl PricePoint {
+    fn new(log: &LogRow, info: &PoolInfo, decimals: (Option<u8>, Option<u8>)) -> Self {
+        Self {
+            block_number: log.block_number,
+            block_timestamp: log.block_timestamp,
+            pool: info.pool,
+            kind: info.kind,
+            token0: info.token0,
+            token1: info.token1,
+            fee: info.fee,
+            reserve0: None,
+            reserve1: None,
+            sqrt_price_x96: None,
+            tick: None,
+            liquidity: None,
+            price_raw: None,
+            decimals0: decimals.0,
+            decimals1: decimals.1,
+            price: None,
+            updates: 0,
+        }
+    }
+
+    /// 以上一行的状态作为 `log` 所在区块的初始状态
+    fn carry(&self, log: &LogRow) -> Self {
+        Self { block_number: log.block_number, block_timestamp: log.block_timestamp, updates: 0, ..self.clone() }
+    }
+
+    /// 以事件给出的完整状态覆盖当前状态
+    fn apply(&mut self, event: &PoolEvent) {
+        match *event {
+            PoolEvent::V2Sync { reserve0, reserve1 } => {
+                self.reserve0 = Some(reserve0);
+                self.reserve1 = Some(reserve1);
+                self.price_raw = (!reserve0.is_zero()).then(|| f64::from(reserve1) / f64::from(reserve0));
+            }
+            PoolEvent::V3Swap { sqrt_price_x96, liquidity, tick, .. } => {
+                self.sqrt_price_x96 = Some(sqrt_price_x96);
+                self.tick = Some(tick);
+                self.liquidity = Some(liquidity);
+                let ratio = f64::from(sqrt_price_x96) / 2f64.powi(96);
+                self.price_raw = Some(ratio * ratio);
+            }
+            PoolEvent::V2Swap { .. } => return,
+        }
+        self.price = match (self.price_raw, self.decimals0, self.decimals1) {
+            (Some(raw), Some(d0), Some(d1)) => Some(raw * 10f64.powi(i32::from(d0) - i32::from(d1))),
+            _ => None,
+        };
+        self.updates += 1;
+    }
+}
+
+/// 由 V2 Sync 与 V3 Swap 重建池子逐块状态，按 (区块, 池子) 顺序回调价格点（稀疏，见 [`PricePoint`]）。
+/// 池子由 `filter` 限定：指定池子，或某些工厂创建的全部池子。
+pub fn scan_prices(
+    db: &BscDatabase,
+    range: Range<u64>,
+    filter: PoolFilter,
+    engine: &ParallelScan,
+    checkpointer: Option<&Checkpointer>,
+    resume: bool,
+    mut on_point: impl FnMut(PricePoint) -> Result<()>,
+) -> Result<()> {
+    if range.is_empty() {
+        return Ok(());
+    }
+    db.ensure_available(DataKind::Receipts, range.start)?;
+    let mut resolver = PoolResolver::new(db, filter.factories);
+    let scanner = LogScanner {
+        filter: LogFilter { addresses: filter.pools, topics: [vec![V2_SYNC_TOPIC, V3_SWAP_TOPIC], vec![], vec![], vec![]] },
+    };
+    // 各池子的最新状态，跨分片延续；恢复时从检查点取回
+    let (range, mut pools) = match checkpointer {
+        Some(ckpt) if resume => {
+            let pools: HashMap<Address, PricePoint> = ckpt.load_state()?.unwrap_or_default();
+            (ckpt.resume_range(range)?, pools)
+        }
+        _ => (range, HashMap::new()),
+    };
+    // 本次扫描内解析失败的地址（非池子或工厂不在白名单），不再重复解析，跳过日志也只记一次
+    let mut unresolved: HashSet<Address> = HashSet::new();
+    // 每个分片内聚合后整体输出，再连同池子状态落盘检查点
+    engine.run(db, range, &scanner, |chunk, logs| {
+        resolver.refresh();
+        let mut points: BTreeMap<(u64, Address), PricePoint> = BTreeMap::new();
+        for log in logs {
+            let Some(event) = PoolEvent::from_log(&log.topics, &log.data) else { continue };
+            let key = (log.block_number, log.address);
+            if !points.contains_key(&key) {
+                let point = match pools.get(&log.address) {
+                    Some(prev) => prev.carry(&log),
+                    None if unresolved.contains(&log.address) => continue,
+                    None => {
+                        let Some(info) = resolver.resolve(log.address, event.kind(), log.block_number)? else {
+                            unresolved.insert(log.address);
+                            continue;
+                        };
+                        let decimals = (
+                            resolver.token_decimals(info.token0, log.block_number)?,
+                            resolver.token_decimals(info.token1, log.block_number)?,
+                        );
+                        PricePoint::new(&log, &info, decimals)
+                    }
+                };
+                points.insert(key, point);
+            }
+            if let Some(point) = points.get_mut(&key) {
+                point.apply(&event);
+                pools.insert(point.pool, point.clone());
+            }
+        }
+        points.into_values().try_for_each(&mut on_point)?;
+        match checkpointer {
+            Some(ckpt) => ckpt.save_with_state(db, chunk.end - 1, Some(&pools)),
+            None => Ok(()),
+        }
+    })
+}
+
+/// 返回 `range`（左闭右开）内的全部价格点
+pub fn price_series(db: &BscDatabase, range: Range<u64>, filter: PoolFilter) -> Result<Vec<PricePoint>> {
+    ParallelScan::default().collect_with(|engine, push| scan_prices(db, range, filter, engine, None, false, push))
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::I256;
+
+    use super::*;
+    use crate::test_support::{e18, log, v2_pool, v3_pool, ROUTER};
+
+    fn point(info: PoolInfo, decimals: (Option<u8>, Option<u8>)) -> PricePoint {
+        PricePoint::new(&log(info.pool, &[], &[]), &info, decimals)
+    }
+
+    fn v3_swap(sqrt_price_x96: U256) -> PoolEvent {
+        PoolEvent::V3Swap {
+            sender: ROUTER,
+            recipient: ROUTER,
+            amount0: I256::ZERO,
+            amount1: I256::ZERO,
+            sqrt_price_x96,
+            liquidity: 7,
+            tick: -69_325,
+        }
+    }
+
+    fn assert_close(actual: Option<f64>, expected: f64) {
+        let actual = actual.expect("price");
+        assert!((actual - expected).abs() <= expected.abs() * 1e-12, "{actual} != {expected}");
+    }
+
+    #[test]
+    fn v2_sync_prices_from_reserves() {
+        // 10,000 WBNB / 6,000,000 BUSD
+        let mut p = point(v2_pool(), (Some(18), Some(18)));
+        p.apply(&PoolEvent::V2Sync { reserve0: e18(10_000), reserve1: e18(6_000_000) });
+        assert_close(p.price_raw, 600.0);
+        assert_close(p.price, 600.0);
+        assert_eq!((p.reserve0, p.reserve1, p.updates), (Some(e18(10_000)), Some(e18(6_000_000)), 1));
+
+        // token0 储备为 0（池子被抽干）时没有价格，但储备照常更新
+        p.apply(&PoolEvent::V2Sync { reserve0: U256::ZERO, reserve1: e18(5) });
+        assert_eq!((p.price_raw, p.price), (None, None));
+        assert_eq!((p.reserve0, p.reserve1, p.updates), (Some(U256::ZERO), Some(e18(5)), 2));
+    }
+
+    #[test]
+    fn v2_swap_carries_no_state() {
+        let mut p = point(v2_pool(), (Some(18), Some(18)));
+        p.apply(&PoolEvent::V2Sync { reserve0: e18(10_000), reserve1: e18(6_000_000) });
+        let swap = PoolEvent::V2Swap {
+            sender: ROUTER,
+            to: ROUTER,
+            amount0_in: U256::ZERO,
+            amount1_in: e18(600),
+            amount0_out: e18(1),
+            amount1_out: U256::ZERO,
+        };
+        p.apply(&swap);
+        assert_eq!(p.updates, 1);
+        assert_close(p.price, 600.0);
+    }
+
+    #[test]
+    fn v3_sqrt_price_is_squared_and_scaled_by_decimals() {
+        // sqrtPriceX96 = 2^91，即 sqrtPrice = 1/32、price = 1/1024
+        let sqrt_price = U256::from(1) << 91;
+
+        let mut p = point(v3_pool(), (Some(18), Some(18)));
+        p.apply(&v3_swap(sqrt_price));
+        assert_close(p.price_raw, 1.0 / 1024.0);
+        assert_close(p.price, 1.0 / 1024.0);
+        assert_eq!((p.sqrt_price_x96, p.tick, p.liquidity), (Some(sqrt_price), Some(-69_325), Some(7)));
+        assert_eq!((p.reserve0, p.reserve1), (None, None));
+
+        // token0 为 6 位小数、token1 为 18 位小数：1 token0 = raw * 10^(6-18) token1
+        let mut p = point(v3_pool(), (Some(6), Some(18)));
+        p.apply(&v3_swap(sqrt_price));
+        assert_close(p.price, 1.0 / 1024.0 * 1e-12);
+
+        // decimals 未知时只给出原始价格
+        let mut p = point(v3_pool(), (None, Some(18)));
+        p.apply(&v3_swap(sqrt_price));
+        assert_close(p.price_raw, 1.0 / 1024.0);
+        assert_eq!(p.price, None);
+    }
+
+    #[test]
+    fn carry_keeps_state_and_resets_block_fields() {
+        let mut p = point(v3_pool(), (Some(18), Some(18)));
+        p.apply(&v3_swap(U256::from(1) << 91));
+        let mut next_log = log(p.pool, &[], &[]);
+        next_log.block_number = p.block_number + 5;
+        next_log.block_timestamp = p.block_timestamp + 15;
+
+        let next = p.carry(&next_log);
+        assert_eq!((next.block_number, next.block_timestamp), (next_log.block_number, next_log.block_timestamp));
+        assert_eq!(next.updates, 0);
+        assert_eq!((next.sqrt_price_x96, next.tick), (p.sqrt_price_x96, p.tick));
+        assert_eq!(next.price, p.price);
+    }
+}
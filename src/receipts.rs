@@ -1,20 +1,42 @@
-use std::path::PathBuf;
-use reth_provider::providers::StaticFileProvider;
+use std::ops::Range;
+
+use alloy_consensus::TxType;
 use reth_static_file_types::StaticFileSegment;
 use reth_db::static_file::ReceiptMask;
-use reth_ethereum_primitives::{EthPrimitives, Receipt};   // Reth 内置的 Receipt 类型
-
-/// 从指定 static_files 目录中，读取包含给定块号的 receipts 段并打印摘要
-pub fn test_receipts(path: impl Into<PathBuf>, block_in_segment: u64) -> eyre::Result<()> {
-    // 0) 兼容传入数据目录或 static_files 目录
-    let p: PathBuf = path.into();
-    let static_dir = {
-        let candidate = p.join("static_files");
-        if candidate.is_dir() { candidate } else { p }
-    };
+use reth_ethereum_primitives::Receipt;   // Reth 内置的 Receipt 类型
+use reth_provider::{BlockBodyIndicesProvider, ReceiptProvider};
+use serde::{Deserialize, Serialize};
 
-    // 1) 打开静态文件提供者（压缩：true）
-    let sf_provider = StaticFileProvider::<EthPrimitives>::read_only(static_dir, true)?;
+use crate::checkpoint::Checkpointer;
+use crate::databases::{BscDatabase, BscProvider};
+use crate::error::{AppError, Result};
+use crate::info::DataKind;
+use crate::output::Record;
+use crate::parallel::{ParallelScan, RangeScanner};
+
+/// ReceiptsTest 输出：static_files 段内单条 receipt 摘要
+#[derive(Debug, Clone, Serialize)]
+pub struct ReceiptRow {
+    pub tx_num: u64,
+    pub tx_type: String,
+    pub success: bool,
+    pub cumulative_gas_used: u64,
+    pub logs: usize,
+}
+
+impl Record for ReceiptRow {
+    fn text(&self) -> String {
+        format!(
+            "tx_num={} tx_type={} success={} cumulative_gas_used={} logs={}",
+            self.tx_num, self.tx_type, self.success, self.cumulative_gas_used, self.logs,
+        )
+    }
+}
+
+/// 从 static_files 中读取包含给定块号的 receipts 段，返回段内全部收据摘要
+pub fn test_receipts(db: &BscDatabase, block_in_segment: u64) -> Result<Vec<ReceiptRow>> {
+    // 1) 使用共享的静态文件提供者（目录由 BscDbConfig 决定）
+    let sf_provider = db.static_file_provider();
 
     // 2) 获取包含该块号的 receipts 段 jar
     let jar = sf_provider.get_segment_provider_from_block(
@@ -24,19 +46,281 @@ pub fn test_receipts(path: impl Into<PathBuf>, block_in_segment: u64) -> eyre::R
     )?;
 
     // 3) 遍历段内所有收据
+    let mut rows = Vec::new();
     let mut cursor = jar.cursor()?;
     let mut tx_num = cursor.jar().user_header().start().unwrap_or_default();
     while let Some(receipt) = cursor.get_one::<ReceiptMask<Receipt>>(tx_num.into())? {
-        println!(
-            "tx_num={} tx_type={:?} success={} cumulative_gas_used={} logs={}",
+        rows.push(ReceiptRow {
             tx_num,
-            receipt.tx_type,
-            receipt.success,
-            receipt.cumulative_gas_used,
-            receipt.logs.len(),
-        );
+            tx_type: format!("{:?}", receipt.tx_type),
+            success: receipt.success,
+            cumulative_gas_used: receipt.cumulative_gas_used,
+            logs: receipt.logs.len(),
+        });
         tx_num += 1;
     }
-    Ok(())
+    Ok(rows)
+}
+
+
+/// 收据所在的存储位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiptSource {
+    StaticFile,
+    Mdbx,
+}
+
+/// 一组收据的累计统计（按块或按段）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReceiptTotals {
+    pub txs: u64,
+    pub failed: u64,
+    /// failed / txs（无交易时为 0）
+    pub failure_rate: f64,
+    pub logs: u64,
+    pub gas_used: u64,
+    pub legacy: u64,
+    pub eip2930: u64,
+    pub eip1559: u64,
+    pub eip4844: u64,
+    pub eip7702: u64,
+}
+
+impl ReceiptTotals {
+    fn push(&mut self, receipt: &Receipt, gas_used: u64) {
+        self.txs += 1;
+        self.failed += u64::from(!receipt.success);
+        self.logs += receipt.logs.len() as u64;
+        self.gas_used += gas_used;
+        match receipt.tx_type {
+            TxType::Legacy => self.legacy += 1,
+            TxType::Eip2930 => self.eip2930 += 1,
+            TxType::Eip1559 => self.eip1559 += 1,
+            TxType::Eip4844 => self.eip4844 += 1,
+            TxType::Eip7702 => self.eip7702 += 1,
+        }
+        self.update_rate();
+    }
+
+    fn merge(&mut self, other: &Self) {
+        self.txs += other.txs;
+        self.failed += other.failed;
+        self.logs += other.logs;
+        self.gas_used += other.gas_used;
+        self.legacy += other.legacy;
+        self.eip2930 += other.eip2930;
+        self.eip1559 += other.eip1559;
+        self.eip4844 += other.eip4844;
+        self.eip7702 += other.eip7702;
+        self.update_rate();
+    }
+
+    fn update_rate(&mut self) {
+        self.failure_rate = if self.txs == 0 { 0.0 } else { self.failed as f64 / self.txs as f64 };
+    }
+
+    fn text(&self) -> String {
+        format!(
+            "txs={} failed={} ({:.2}%) logs={} gas_used={} types=legacy:{}/2930:{}/1559:{}/4844:{}/7702:{}",
+            self.txs,
+            self.failed,
+            self.failure_rate * 100.0,
+            self.logs,
+            self.gas_used,
+            self.legacy,
+            self.eip2930,
+            self.eip1559,
+            self.eip4844,
+            self.eip7702,
+        )
+    }
+}
+
+/// 单个区块的收据统计
+#[derive(Debug, Clone, Serialize)]
+pub struct ReceiptBlockStats {
+    pub block_number: u64,
+    pub source: ReceiptSource,
+    /// 收据被部分裁剪（如按日志过滤裁剪），未计入统计
+    pub skipped: bool,
+    #[serde(flatten)]
+    pub totals: ReceiptTotals,
+}
+
+impl Record for ReceiptBlockStats {
+    fn text(&self) -> String {
+        if self.skipped {
+            return format!("block={} source={:?} skipped (receipts pruned)", self.block_number, self.source);
+        }
+        format!("block={} source={:?} {}", self.block_number, self.source, self.totals.text())
+    }
+}
+
+/// 单个 static_files 段（或 MDBX 尾部）的收据统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReceiptRangeStats {
+    pub source: ReceiptSource,
+    pub first_block: u64,
+    pub last_block: u64,
+    /// 计入统计的区块数
+    pub blocks: u64,
+    /// 收据被部分裁剪而跳过的区块数
+    pub skipped_blocks: u64,
+    #[serde(flatten)]
+    pub totals: ReceiptTotals,
+}
+
+impl Record for ReceiptRangeStats {
+    fn text(&self) -> String {
+        format!(
+            "{:?} blocks {}..={} ({} blocks, {} skipped) {}",
+            self.source,
+            self.first_block,
+            self.last_block,
+            self.blocks,
+            self.skipped_blocks,
+            self.totals.text()
+        )
+    }
 }
 
+/// 逐块读取收据（provider 自动在 static_files 与 MDBX 之间路由）
+pub struct ReceiptStatsScanner {
+    /// static_files 中 receipts 段的区块区间（闭区间）
+    pub static_ranges: Vec<(u64, u64)>,
+}
+
+impl ReceiptStatsScanner {
+    fn source(&self, block: u64) -> ReceiptSource {
+        if self.static_ranges.iter().any(|&(start, end)| start <= block && block <= end) {
+            ReceiptSource::StaticFile
+        } else {
+            ReceiptSource::Mdbx
+        }
+    }
+}
+
+impl RangeScanner for ReceiptStatsScanner {
+    type Output = ReceiptBlockStats;
+
+    fn scan_chunk(&self, provider: BscProvider, range: Range<u64>) -> Result<Vec<ReceiptBlockStats>> {
+        let mut rows = Vec::with_capacity((range.end - range.start) as usize);
+        for number in range {
+            let body = provider
+                .block_body_indices(number)?
+                .ok_or_else(|| AppError::NotFound(format!("block body {number}")))?;
+            let receipts: Vec<Receipt> = provider.receipts_by_tx_range(body.tx_num_range())?;
+            let source = self.source(number);
+            if receipts.len() as u64 != body.tx_count() {
+                // 按日志过滤裁剪只保留部分收据，整块跳过
+                let totals = ReceiptTotals::default();
+                rows.push(ReceiptBlockStats { block_number: number, source, skipped: true, totals });
+                continue;
+            }
+            let mut totals = ReceiptTotals::default();
+            let mut prev_cumulative = 0;
+            for receipt in &receipts {
+                totals.push(receipt, receipt.cumulative_gas_used.saturating_sub(prev_cumulative));
+                prev_cumulative = receipt.cumulative_gas_used;
+            }
+            rows.push(ReceiptBlockStats { block_number: number, source, skipped: false, totals });
+        }
+        Ok(rows)
+    }
+}
+
+/// 把扫描范围按 receipts 段切分：每个 static_files 段一段，其后为 MDBX 尾部
+fn receipt_ranges(db: &BscDatabase, range: &Range<u64>) -> Result<(Vec<(u64, u64)>, Vec<ReceiptRangeStats>)> {
+    let segment = StaticFileSegment::Receipts.as_str();
+    let mut static_ranges: Vec<(u64, u64)> = db
+        .static_file_segments()?
+        .into_iter()
+        .filter(|f| f.segment == segment)
+        .filter_map(|f| f.block_range)
+        .collect();
+    static_ranges.sort_unstable();
+
+    let empty = |source, first_block, last_block| ReceiptRangeStats {
+        source,
+        first_block,
+        last_block,
+        blocks: 0,
+        skipped_blocks: 0,
+        totals: ReceiptTotals::default(),
+    };
+    let last = range.end - 1;
+    let mut ranges: Vec<ReceiptRangeStats> = static_ranges
+        .iter()
+        .filter(|&&(start, end)| start <= last && end >= range.start)
+        .map(|&(start, end)| empty(ReceiptSource::StaticFile, start.max(range.start), end.min(last)))
+        .collect();
+    let tail_start = static_ranges.last().map_or(range.start, |&(_, end)| (end + 1).max(range.start));
+    if tail_start <= last {
+        ranges.push(empty(ReceiptSource::Mdbx, tail_start, last));
+    }
+    Ok((static_ranges, ranges))
+}
+
+/// 遍历全部 receipts static_files 段与 MDBX 尾部，逐块回调统计，返回按段汇总的统计。
+/// 起点会裁剪到 receipts 的可用区间；段汇总随检查点保存，从检查点继续时在其基础上累加。
+pub fn receipt_stats(
+    db: &BscDatabase,
+    range: Range<u64>,
+    engine: &ParallelScan,
+    checkpointer: Option<&Checkpointer>,
+    resume: bool,
+    mut on_block: impl FnMut(&ReceiptBlockStats) -> Result<()>,
+) -> Result<Vec<ReceiptRangeStats>> {
+    let available = db
+        .cached_availability()?
+        .range(DataKind::Receipts)
+        .and_then(|r| r.first)
+        .ok_or(AppError::Pruned { kind: DataKind::Receipts, block: range.start })?;
+    let range = range.start.max(available)..range.end;
+    if range.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let (static_ranges, mut ranges) = receipt_ranges(db, &range)?;
+    let scan_range = match checkpointer {
+        Some(ckpt) if resume => {
+            // 已保存的段汇总并入当前的分段（上次的 MDBX 尾部可能已迁入 static_files）
+            let saved: Vec<ReceiptRangeStats> = ckpt.load_state()?.unwrap_or_default();
+            for prev in &saved {
+                let target = ranges.iter_mut().find(|r| r.first_block <= prev.first_block && prev.first_block <= r.last_block);
+                if let Some(r) = target {
+                    r.blocks += prev.blocks;
+                    r.skipped_blocks += prev.skipped_blocks;
+                    r.totals.merge(&prev.totals);
+                }
+            }
+            ckpt.resume_range(range)?
+        }
+        _ => range,
+    };
+    let scanner = ReceiptStatsScanner { static_ranges };
+    let mut current = 0;
+    engine.run(db, scan_range, &scanner, |chunk, blocks| {
+        for block in blocks {
+            while ranges.get(current).is_some_and(|r| r.last_block < block.block_number) {
+                current += 1;
+            }
+            if let Some(r) = ranges.get_mut(current).filter(|r| r.first_block <= block.block_number) {
+                if block.skipped {
+                    r.skipped_blocks += 1;
+                } else {
+                    r.blocks += 1;
+                    r.totals.merge(&block.totals);
+                }
+            }
+            on_block(&block)?;
+        }
+        match checkpointer {
+            Some(ckpt) => ckpt.save_with_state(db, chunk.end - 1, Some(&ranges)),
+            None => Ok(()),
+        }
+    })?;
+    ranges.retain(|r| r.blocks > 0 || r.skipped_blocks > 0);
+    Ok(ranges)
+}
@@ -1,47 +1,100 @@
-use reth_db::{
-    cursor::DbCursorRO,
-    tables,
-    transaction::DbTx,
-    open_db_read_only,
-    mdbx::DatabaseArguments,
-    Database,
-};
-use std::{path::Path, sync::Arc};
-use crate::databases::BscDatabase;
+use std::ops::Range;
+
+use alloy_primitives::{Address, B256};
+use crate::blocks::BlockIter;
+use crate::checkpoint::Checkpointer;
+use crate::databases::{BscDatabase, BscProvider};
+use crate::error::Result;
+use crate::output::{CreationFlagRow, Record};
+use serde::Serialize;
+use crate::parallel::{ParallelScan, RangeScanner};
 use alloy_consensus::transaction::Transaction; // bring to() into scope
 
-fn scan_contract_creations_raw<T: DbTx>(tx: &T) -> eyre::Result<()> {
-    let mut cursor = tx.cursor_read::<tables::Transactions>()?;
-    while let Some((tx_num, t)) = cursor.next()? {
-        if t.to().is_none() {
-            // 这里处理合约创建交易（to 为 None）
-            tracing::debug!(tx_num, "contract creation tx detected");
+/// 一笔合约创建交易
+#[derive(Debug, Clone, Serialize)]
+pub struct ContractCreation {
+    pub block_number: u64,
+    pub tx_index: u32,
+    pub tx_num: u64,
+    pub hash: B256,
+    /// 创建者（senders 被裁剪时为 None）
+    pub creator: Option<Address>,
+}
+
+impl Record for ContractCreation {
+    fn text(&self) -> String {
+        format!(
+            "block={} tx_num={} hash={:#x} creator={}",
+            self.block_number,
+            self.tx_num,
+            self.hash,
+            self.creator.map(|a| format!("{a:#x}")).unwrap_or_else(|| "unknown".to_string()),
+        )
+    }
+}
+
+/// 在区块分片内查找 to == None 的交易
+pub struct ContractCreationScanner;
+
+impl RangeScanner for ContractCreationScanner {
+    type Output = ContractCreation;
+
+    fn scan_chunk(&self, provider: BscProvider, range: Range<u64>) -> Result<Vec<ContractCreation>> {
+        let mut found = Vec::new();
+        for bundle in BlockIter::new(provider, range.start, range.end) {
+            let bundle = bundle?;
+            for (i, t) in bundle.transactions.iter().enumerate() {
+                if t.to().is_none() {
+                    found.push(ContractCreation {
+                        block_number: bundle.number,
+                        tx_index: i as u32,
+                        tx_num: bundle.first_tx_num + i as u64,
+                        hash: *t.hash(),
+                        creator: bundle.senders.as_ref().and_then(|s| s.get(i).copied()),
+                    });
+                }
+            }
         }
+        Ok(found)
     }
-    Ok(())
 }
 
-/// 打开只读 MDBX 并扫描所有合约创建交易
-pub fn scan_contract_creations(datadir: impl AsRef<Path>) -> eyre::Result<()> {
-    let datadir = datadir.as_ref();
-    let db_path = datadir.join("db");
-    let db = Arc::new(open_db_read_only(&db_path, DatabaseArguments::default())?);
-    let tx = db.tx()?;
-    scan_contract_creations_raw(&tx)
+/// 并行扫描区块范围内所有合约创建交易，按区块顺序回调；
+/// 提供 checkpointer 时每个分片完成后落盘进度，`resume` 时从上次进度继续
+pub fn scan_contract_creations(
+    db: &BscDatabase,
+    range: Range<u64>,
+    engine: &ParallelScan,
+    checkpointer: Option<&Checkpointer>,
+    resume: bool,
+    mut on_creation: impl FnMut(ContractCreation) -> Result<()>,
+) -> Result<()> {
+    engine.run_checkpointed(db, range, &ContractCreationScanner, checkpointer, resume, |_, found| {
+        found.into_iter().try_for_each(&mut on_creation)
+    })
 }
 
 /// 扫描指定区块内的所有交易，判定是否为合约创建（to == None）
-pub fn scan_block_contract_creations(db: &BscDatabase, block_number: u64) -> eyre::Result<Vec<bool>> {
+pub fn scan_block_contract_creations(db: &BscDatabase, block_number: u64) -> Result<Vec<bool>> {
     let txs = db.query_block_order_transactions(block_number)?;
     let flags = txs.iter().map(|t| t.to().is_none()).collect();
     Ok(flags)
 }
 
-/// 打印版：按序输出 idx 与是否合约创建
-pub fn print_block_contract_creations(db: &BscDatabase, block_number: u64) -> eyre::Result<()> {
-    let flags = scan_block_contract_creations(db, block_number)?;
-    for (i, is_create) in flags.iter().copied().enumerate() {
-        println!("{}: {}", i, if is_create { "CREATE" } else { "CALL" });
+/// 输出行版本（供 --format 使用）；`exclude_system` 时跳过 BSC 系统交易
+pub fn block_contract_creation_rows(
+    db: &BscDatabase,
+    block_number: u64,
+    exclude_system: bool,
+) -> Result<Vec<CreationFlagRow>> {
+    if exclude_system {
+        let txs = crate::al::analyze_block(db, block_number)?;
+        return Ok(txs
+            .into_iter()
+            .filter(|t| !t.is_system())
+            .map(|t| CreationFlagRow { index: t.index as usize, is_create: t.to.is_none() })
+            .collect());
     }
-    Ok(())
+    let flags = scan_block_contract_creations(db, block_number)?;
+    Ok(flags.into_iter().enumerate().map(|(index, is_create)| CreationFlagRow { index, is_create }).collect())
 }
@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use alloy_consensus::transaction::Transaction; // to()/input()/effective_gas_price()
+use alloy_primitives::{Address, Selector};
+use serde::Serialize;
+
+use crate::blocks::BlockIter;
+use crate::databases::{BscDatabase, BscProvider};
+use crate::error::Result;
+use crate::output::Record;
+use crate::parallel::{ParallelScan, RangeScanner};
+use crate::signatures::SignatureDb;
+use crate::system::system_contract_name;
+
+/// 单个 (合约, selector) 的调用统计；按区块范围汇总时 contract 为 None
+#[derive(Debug, Clone, Serialize)]
+pub struct SelectorStat {
+    pub contract: Option<Address>,
+    pub selector: Selector,
+    /// 签名库中的候选签名（未收录为 None）
+    pub signature: Option<String>,
+    pub calls: u64,
+    /// 失败调用数（receipts 被裁剪的交易不计入）
+    pub failed: u64,
+    /// 成功与失败调用的 gas_used 之和（receipts 被裁剪的交易不计入）
+    pub gas_used: u64,
+}
+
+impl Record for SelectorStat {
+    fn text(&self) -> String {
+        let contract = self.contract.map(|c| format!("{c:#x} ")).unwrap_or_default();
+        format!(
+            "{contract}{} {:<48} calls={} failed={} gas_used={}",
+            self.selector,
+            self.signature.as_deref().unwrap_or("?"),
+            self.calls,
+            self.failed,
+            self.gas_used,
+        )
+    }
+}
+
+/// 统计选项
+#[derive(Debug, Clone, Default)]
+pub struct SelectorStatsOptions {
+    /// 按被调用合约分组（否则整个区块范围汇总）
+    pub by_contract: bool,
+    /// 只统计这些合约（为空表示全部）
+    pub contracts: Vec<Address>,
+    pub exclude_system: bool,
+}
+
+type StatKey = (Option<Address>, Selector);
+
+/// 逐块统计 calldata selector，分片内先聚合。
+/// 只读取交易的 `to` 与 calldata 前 4 字节，不做签名恢复；失败数与 gas 取自回执。
+pub struct SelectorScanner {
+    pub options: SelectorStatsOptions,
+}
+
+impl RangeScanner for SelectorScanner {
+    type Output = SelectorStat;
+
+    fn scan_chunk(&self, provider: BscProvider, range: Range<u64>) -> Result<Vec<SelectorStat>> {
+        let mut stats: HashMap<StatKey, SelectorStat> = HashMap::new();
+        for bundle in BlockIter::new(provider, range.start, range.end) {
+            let bundle = bundle?;
+            let coinbase = bundle.header.beneficiary;
+            let base_fee = bundle.header.base_fee_per_gas;
+            let mut prev_cumulative = 0u64;
+            for (i, tx) in bundle.transactions.iter().enumerate() {
+                // 由累计 gas 推算本笔 gas_used（receipts 被裁剪时为 None）
+                let receipt = bundle.receipts.as_ref().and_then(|r| r.get(i)).map(|r| {
+                    let used = r.cumulative_gas_used.saturating_sub(prev_cumulative);
+                    prev_cumulative = r.cumulative_gas_used;
+                    (used, r.success)
+                });
+                let (Some(to), Some(selector)) = (tx.to(), tx.input().get(..4).map(Selector::from_slice)) else {
+                    continue;
+                };
+                if self.options.exclude_system {
+                    // 系统交易由 coinbase 以 0 gas 价格发往系统合约；senders 被裁剪时不比较发送者
+                    let sender = bundle.senders.as_ref().and_then(|s| s.get(i));
+                    let system = tx.effective_gas_price(base_fee) == 0
+                        && system_contract_name(to).is_some()
+                        && sender.is_none_or(|&from| from == coinbase);
+                    if system {
+                        continue;
+                    }
+                }
+                if !self.options.contracts.is_empty() && !self.options.contracts.contains(&to) {
+                    continue;
+                }
+                let contract = self.options.by_contract.then_some(to);
+                let stat = stats.entry((contract, selector)).or_insert_with(|| SelectorStat {
+                    contract,
+                    selector,
+                    signature: None,
+                    calls: 0,
+                    failed: 0,
+                    gas_used: 0,
+                });
+                stat.calls += 1;
+                stat.failed += u64::from(receipt.is_some_and(|(_, ok)| !ok));
+                stat.gas_used += receipt.map(|(used, _)| used).unwrap_or_default();
+            }
+        }
+        Ok(stats.into_values().collect())
+    }
+}
+
+/// 统计区块范围内最常被调用的函数。
+/// 结果按调用次数降序；`top` 限制每个合约（或整个范围）的条数，签名由 `signatures` 解析。
+pub fn selector_stats(
+    db: &BscDatabase,
+    range: Range<u64>,
+    engine: &ParallelScan,
+    options: SelectorStatsOptions,
+    signatures: &SignatureDb,
+    top: usize,
+) -> Result<Vec<SelectorStat>> {
+    let mut merged: HashMap<StatKey, SelectorStat> = HashMap::new();
+    engine.run(db, range, &SelectorScanner { options }, |_, partial| {
+        for stat in partial {
+            match merged.get_mut(&(stat.contract, stat.selector)) {
+                Some(total) => {
+                    total.calls += stat.calls;
+                    total.failed += stat.failed;
+                    total.gas_used += stat.gas_used;
+                }
+                None => {
+                    merged.insert((stat.contract, stat.selector), stat);
+                }
+            }
+        }
+        Ok(())
+    })?;
+
+    let mut all: Vec<SelectorStat> = merged.into_values().collect();
+    all.sort_by(|a, b| a.contract.cmp(&b.contract).then(b.calls.cmp(&a.calls)).then(a.selector.cmp(&b.selector)));
+    let mut result = Vec::new();
+    let mut per_contract = 0usize;
+    let mut current = None;
+    for mut stat in all {
+        if current != Some(stat.contract) {
+            current = Some(stat.contract);
+            per_contract = 0;
+        }
+        if per_contract < top {
+            stat.signature = signatures.name(stat.selector);
+            result.push(stat);
+        }
+        per_contract += 1;
+    }
+    Ok(result)
+}
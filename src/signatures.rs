@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use alloy_primitives::{hex, keccak256, Selector};
+use eyre::Context;
+use serde_json::Value;
+
+use crate::error::{AppError, Result};
+
+/// 内置的常用函数签名（BEP-20/721/1155、WBNB、PancakeSwap 路由与池子、Multicall、BSC 系统合约）
+const BUNDLED_SIGNATURES: &[&str] = &[
+    // BEP-20
+    "transfer(address,uint256)",
+    "transferFrom(address,address,uint256)",
+    "approve(address,uint256)",
+    "increaseAllowance(address,uint256)",
+    "decreaseAllowance(address,uint256)",
+    "permit(address,address,uint256,uint256,uint8,bytes32,bytes32)",
+    "mint(address,uint256)",
+    "burn(uint256)",
+    // BEP-721 / BEP-1155
+    "safeTransferFrom(address,address,uint256)",
+    "safeTransferFrom(address,address,uint256,bytes)",
+    "setApprovalForAll(address,bool)",
+    "safeTransferFrom(address,address,uint256,uint256,bytes)",
+    "safeBatchTransferFrom(address,address,uint256[],uint256[],bytes)",
+    // WBNB
+    "deposit()",
+    "withdraw(uint256)",
+    // PancakeSwap V2 Router
+    "swapExactTokensForTokens(uint256,uint256,address[],address,uint256)",
+    "swapTokensForExactTokens(uint256,uint256,address[],address,uint256)",
+    "swapExactETHForTokens(uint256,address[],address,uint256)",
+    "swapTokensForExactETH(uint256,uint256,address[],address,uint256)",
+    "swapExactTokensForETH(uint256,uint256,address[],address,uint256)",
+    "swapETHForExactTokens(uint256,address[],address,uint256)",
+    "swapExactTokensForTokensSupportingFeeOnTransferTokens(uint256,uint256,address[],address,uint256)",
+    "swapExactETHForTokensSupportingFeeOnTransferTokens(uint256,address[],address,uint256)",
+    "swapExactTokensForETHSupportingFeeOnTransferTokens(uint256,uint256,address[],address,uint256)",
+    "addLiquidity(address,address,uint256,uint256,uint256,uint256,address,uint256)",
+    "addLiquidityETH(address,uint256,uint256,uint256,address,uint256)",
+    "removeLiquidity(address,address,uint256,uint256,uint256,address,uint256)",
+    "removeLiquidityETH(address,uint256,uint256,uint256,address,uint256)",
+    // PancakeSwap V2 Pair
+    "swap(uint256,uint256,address,bytes)",
+    "sync()",
+    "skim(address)",
+    // PancakeSwap V3 / Smart Router
+    "exactInputSingle((address,address,uint24,address,uint256,uint256,uint160))",
+    "exactInput((bytes,address,uint256,uint256))",
+    "exactOutputSingle((address,address,uint24,address,uint256,uint256,uint160))",
+    "exactOutput((bytes,address,uint256,uint256))",
+    "multicall(bytes[])",
+    "multicall(uint256,bytes[])",
+    "execute(bytes,bytes[],uint256)",
+    "execute(bytes,bytes[])",
+    // Multicall
+    "aggregate((address,bytes)[])",
+    "tryAggregate(bool,(address,bytes)[])",
+    "aggregate3((address,bool,bytes)[])",
+    // BSC 系统合约
+    "deposit(address)",
+    "slash(address)",
+    "distributeFinalityReward(address[],uint256[])",
+    "updateValidatorSetV2(address[],uint64[],bytes[])",
+    "init()",
+    "delegate(address,bool)",
+    "undelegate(address,uint256)",
+    "redelegate(address,address,uint256,bool)",
+    "claim(address,uint256)",
+];
+
+/// 计算文本签名的 4 字节 selector
+pub fn selector_of(signature: &str) -> Selector {
+    Selector::from_slice(&keccak256(signature.as_bytes())[..4])
+}
+
+/// 本地函数签名库：selector -> 候选文本签名（可能存在碰撞）
+#[derive(Debug, Clone, Default)]
+pub struct SignatureDb {
+    map: HashMap<Selector, Vec<String>>,
+}
+
+impl SignatureDb {
+    /// 仅含内置签名
+    pub fn bundled() -> Self {
+        let mut db = Self::default();
+        for sig in BUNDLED_SIGNATURES {
+            db.insert(sig);
+        }
+        db
+    }
+
+    /// 插入文本签名（去除空白），返回其 selector
+    pub fn insert(&mut self, signature: &str) -> Selector {
+        let signature: String = signature.split_whitespace().collect();
+        let selector = selector_of(&signature);
+        let entry = self.map.entry(selector).or_default();
+        if !entry.contains(&signature) {
+            entry.push(signature);
+        }
+        selector
+    }
+
+    /// 导入签名文件（按扩展名识别）：
+    /// - `.json`：`{"0xa9059cbb": "transfer(address,uint256)"}`（值也可为数组），或签名字符串数组
+    /// - `.csv`：每行 `selector,signature` 或仅 `signature`，允许表头
+    ///
+    /// 声明的 selector 与签名哈希不符的条目会被跳过。返回导入条数。
+    pub fn import(&mut self, path: &Path) -> Result<usize> {
+        let raw = std::fs::read_to_string(path).with_context(|| format!("read signature file {}", path.display()))?;
+        let entries: Vec<(Option<String>, String)> = match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => parse_json(&raw).map_err(|e| {
+                AppError::InvalidArg(format!("signature file {}: {e}", path.display()))
+            })?,
+            Some("csv") => parse_csv(&raw, 4),
+            _ => {
+                return Err(AppError::InvalidArg(format!(
+                    "signature file {} must have a .json or .csv extension",
+                    path.display()
+                )));
+            }
+        };
+
+        let (mut imported, mut skipped) = (0usize, 0usize);
+        for (declared, signature) in entries {
+            let expected = selector_of(&signature.split_whitespace().collect::<String>());
+            let matches = declared
+                .as_deref()
+                .is_none_or(|d| d.trim_start_matches("0x").eq_ignore_ascii_case(&hex::encode(expected)));
+            if matches {
+                self.insert(&signature);
+                imported += 1;
+            } else {
+                skipped += 1;
+            }
+        }
+        if skipped > 0 {
+            tracing::warn!(file = %path.display(), skipped, "Skipped signatures whose selector does not match");
+        }
+        tracing::info!(file = %path.display(), imported, "Imported function signatures");
+        Ok(imported)
+    }
+
+    /// 查找 selector 的候选签名
+    pub fn lookup(&self, selector: Selector) -> &[String] {
+        self.map.get(&selector).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// 候选签名拼接为单个字符串（碰撞时以 ` | ` 分隔）
+    pub fn name(&self, selector: Selector) -> Option<String> {
+        let candidates = self.lookup(selector);
+        (!candidates.is_empty()).then(|| candidates.join(" | "))
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.values().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+}
+
+/// 解析 JSON 签名文件：`{"<key>": "<signature>" | ["<signature>", ...]}` 或签名字符串数组
+pub(crate) fn parse_json(raw: &str) -> std::result::Result<Vec<(Option<String>, String)>, String> {
+    let value: Value = serde_json::from_str(raw).map_err(|e| e.to_string())?;
+    let text = |v: &Value| v.as_str().map(str::to_string).ok_or_else(|| format!("expected string, got {v}"));
+    match value {
+        Value::Object(map) => {
+            let mut entries = Vec::new();
+            for (selector, sigs) in map {
+                match sigs {
+                    Value::Array(list) => {
+                        for sig in &list {
+                            entries.push((Some(selector.clone()), text(sig)?));
+                        }
+                    }
+                    other => entries.push((Some(selector), text(&other)?)),
+                }
+            }
+            Ok(entries)
+        }
+        Value::Array(list) => list.iter().map(|sig| Ok((None, text(sig)?))).collect(),
+        _ => Err("expected an object or an array".to_string()),
+    }
+}
+
+/// 解析 CSV 签名文件：每行 `<key>,<signature>` 或仅 `<signature>`；key 为 `key_bytes` 字节的 0x 十六进制
+pub(crate) fn parse_csv(raw: &str, key_bytes: usize) -> Vec<(Option<String>, String)> {
+    raw.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            // 签名本身含逗号：第一列形如 0x + 十六进制 key 时才视为 selector/topic 列
+            let (first, rest) = line.split_once(',').unwrap_or((line, ""));
+            let first = first.trim().trim_matches('"');
+            let is_selector = first.len() == 2 + key_bytes * 2
+                && first.starts_with("0x")
+                && first[2..].chars().all(|c| c.is_ascii_hexdigit());
+            if is_selector {
+                Some((Some(first.to_string()), rest.trim().trim_matches('"').to_string()))
+            } else if line.contains('(') {
+                Some((None, line.trim_matches('"').to_string()))
+            } else {
+                None // 表头
+            }
+        })
+        .collect()
+}
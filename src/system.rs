@@ -0,0 +1,80 @@
+use std::sync::LazyLock;
+
+use alloy_primitives::{address, keccak256, Address, Selector, U256};
+use serde::Serialize;
+
+/// BSC 系统合约（创世预置，地址固定）
+#[derive(Debug, Clone, Copy)]
+pub struct SystemContract {
+    pub address: Address,
+    pub name: &'static str,
+}
+
+pub const SYSTEM_CONTRACTS: &[SystemContract] = &[
+    SystemContract { address: address!("0x0000000000000000000000000000000000001000"), name: "ValidatorSet" },
+    SystemContract { address: address!("0x0000000000000000000000000000000000001001"), name: "SlashIndicator" },
+    SystemContract { address: address!("0x0000000000000000000000000000000000001002"), name: "SystemReward" },
+    SystemContract { address: address!("0x0000000000000000000000000000000000001003"), name: "LightClient" },
+    SystemContract { address: address!("0x0000000000000000000000000000000000001004"), name: "TokenHub" },
+    SystemContract { address: address!("0x0000000000000000000000000000000000001005"), name: "RelayerIncentivize" },
+    SystemContract { address: address!("0x0000000000000000000000000000000000001006"), name: "RelayerHub" },
+    SystemContract { address: address!("0x0000000000000000000000000000000000001007"), name: "GovHub" },
+    SystemContract { address: address!("0x0000000000000000000000000000000000001008"), name: "TokenManager" },
+    SystemContract { address: address!("0x0000000000000000000000000000000000002000"), name: "CrossChain" },
+    SystemContract { address: address!("0x0000000000000000000000000000000000002001"), name: "Staking" },
+    SystemContract { address: address!("0x0000000000000000000000000000000000002002"), name: "StakeHub" },
+    SystemContract { address: address!("0x0000000000000000000000000000000000002003"), name: "StakeCredit" },
+    SystemContract { address: address!("0x0000000000000000000000000000000000002004"), name: "Governor" },
+    SystemContract { address: address!("0x0000000000000000000000000000000000002005"), name: "GovToken" },
+    SystemContract { address: address!("0x0000000000000000000000000000000000002006"), name: "Timelock" },
+    SystemContract { address: address!("0x0000000000000000000000000000000000003000"), name: "TokenRecoverPortal" },
+];
+
+/// 共识引擎以系统交易形式调用的函数
+const SYSTEM_FUNCTIONS: &[&str] = &[
+    "deposit(address)",
+    "slash(address)",
+    "distributeFinalityReward(address[],uint256[])",
+    "updateValidatorSetV2(address[],uint64[],bytes[])",
+    "init()",
+];
+
+static SYSTEM_SELECTORS: LazyLock<Vec<(Selector, &'static str)>> = LazyLock::new(|| {
+    SYSTEM_FUNCTIONS
+        .iter()
+        .map(|sig| (Selector::from_slice(&keccak256(sig.as_bytes())[..4]), *sig))
+        .collect()
+});
+
+/// 系统合约名称
+pub fn system_contract_name(address: Address) -> Option<&'static str> {
+    SYSTEM_CONTRACTS.iter().find(|c| c.address == address).map(|c| c.name)
+}
+
+/// 系统函数签名
+pub fn system_function_name(selector: Selector) -> Option<&'static str> {
+    SYSTEM_SELECTORS.iter().find(|(s, _)| *s == selector).map(|(_, sig)| *sig)
+}
+
+/// 系统交易命中的合约与函数
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct SystemCall {
+    pub contract: &'static str,
+    /// 未收录的 selector 为 None
+    pub function: Option<&'static str>,
+}
+
+/// 判定系统交易：由出块者（coinbase）发往系统合约，且 gas 价格为 0
+pub fn classify(
+    coinbase: Address,
+    from: Address,
+    to: Option<Address>,
+    effective_gas_price: U256,
+    selector: Option<Selector>,
+) -> Option<SystemCall> {
+    if from != coinbase || !effective_gas_price.is_zero() {
+        return None;
+    }
+    let contract = system_contract_name(to?)?;
+    Some(SystemCall { contract, function: selector.and_then(system_function_name) })
+}
@@ -0,0 +1,66 @@
+//! 单元测试共用的 BSC 主网地址与构造器
+
+use std::path::PathBuf;
+
+use alloy_primitives::{address, Address, Bytes, B256, U256};
+
+use crate::dex::{PoolInfo, PoolKind, PANCAKE_V2_FACTORY, PANCAKE_V3_FACTORY, V2_FEE};
+use crate::logs::LogRow;
+
+pub const WBNB: Address = address!("bb4CdB9CBd36B01bD1cBaEBF2De08d9173bc095c");
+pub const BUSD: Address = address!("e9e7CEA3DedcA5984780Bafc599bD69ADd087D56");
+pub const USDT: Address = address!("55d398326f99059fF775485246999027B3197955");
+/// PancakeSwap V2 Router
+pub const ROUTER: Address = address!("10ED43C718714eb63d5aA57B78B54704E256024E");
+/// PancakeSwap V2 WBNB/BUSD Pair（token0 = WBNB）
+pub const V2_PAIR: Address = address!("58F876857a02D6762E0101bb5C46A8c1ED44Dc16");
+/// PancakeSwap V3 USDT/WBNB 0.05% 池子（token0 = USDT）
+pub const V3_POOL: Address = address!("36696169C63e42cd08ce11f5deeBbCeBae652050");
+
+/// 测试独占的空临时目录（按名称与进程号区分）
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("bsc-scan-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("create temp dir");
+    dir
+}
+
+/// 只带地址、topics 与 data 的日志；区块与交易字段取固定值
+pub fn log(address: Address, topics: &[B256], data: &[u8]) -> LogRow {
+    LogRow {
+        address,
+        topics: topics.to_vec(),
+        data: Bytes::copy_from_slice(data),
+        block_number: 40_000_000,
+        block_hash: B256::ZERO,
+        block_timestamp: 1_719_400_000,
+        transaction_hash: B256::ZERO,
+        transaction_index: 0,
+        log_index: 0,
+        removed: false,
+        event: None,
+        decoded: None,
+    }
+}
+
+/// 依次拼接的 32 字节大端字（静态 ABI 参数的编码）
+pub fn words(values: &[U256]) -> Vec<u8> {
+    values.iter().flat_map(|v| v.to_be_bytes::<32>()).collect()
+}
+
+/// 18 位小数代币的 `n` 个整币
+pub fn e18(n: u64) -> U256 {
+    U256::from(n) * U256::from(10u64).pow(U256::from(18))
+}
+
+/// [`V2_PAIR`] 的池子元数据
+pub fn v2_pool() -> PoolInfo {
+    let (factory, fee) = (PANCAKE_V2_FACTORY, V2_FEE);
+    PoolInfo { pool: V2_PAIR, kind: PoolKind::V2, factory, token0: WBNB, token1: BUSD, fee }
+}
+
+/// [`V3_POOL`] 的池子元数据
+pub fn v3_pool() -> PoolInfo {
+    let factory = PANCAKE_V3_FACTORY;
+    PoolInfo { pool: V3_POOL, kind: PoolKind::V3, factory, token0: USDT, token1: WBNB, fee: 500 }
+}
@@ -0,0 +1,140 @@
+use std::ops::Range;
+
+use alloy_primitives::{b256, Address, B256, U256};
+use serde::Serialize;
+
+use crate::checkpoint::Checkpointer;
+use crate::databases::BscDatabase;
+use crate::error::Result;
+use crate::logs::{scan_logs, LogFilter, LogRow};
+use crate::output::Record;
+use crate::parallel::ParallelScan;
+
+/// keccak256("Transfer(address,address,uint256)")，BEP-20 与 ERC-721 共用
+pub const TRANSFER_TOPIC: B256 = b256!("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef");
+
+/// 一笔 BEP-20 转账
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenTransfer {
+    pub block_number: u64,
+    pub transaction_hash: B256,
+    pub log_index: u64,
+    pub token: Address,
+    pub from: Address,
+    pub to: Address,
+    pub amount: U256,
+}
+
+impl Record for TokenTransfer {
+    fn text(&self) -> String {
+        format!(
+            "block={} tx={:#x} log_index={} token={:#x} {:#x} -> {:#x} amount={}",
+            self.block_number, self.transaction_hash, self.log_index, self.token, self.from, self.to, self.amount,
+        )
+    }
+}
+
+/// indexed 的 address 参数：topic 的低 20 字节
+pub(crate) fn topic_address(topic: &B256) -> Address {
+    Address::from_word(*topic)
+}
+
+impl TokenTransfer {
+    /// 按 BEP-20 形状解码：3 个 topic（from、to indexed）+ 32 字节 amount。
+    /// ERC-721 的 Transfer 有 4 个 topic（tokenId indexed）且 data 为空，返回 None。
+    pub fn from_log(log: &LogRow) -> Option<Self> {
+        if log.topics.len() != 3 || log.topics[0] != TRANSFER_TOPIC || log.data.len() != 32 {
+            return None;
+        }
+        Some(Self {
+            block_number: log.block_number,
+            transaction_hash: log.transaction_hash,
+            log_index: log.log_index,
+            token: log.address,
+            from: topic_address(&log.topics[1]),
+            to: topic_address(&log.topics[2]),
+            amount: U256::from_be_slice(&log.data),
+        })
+    }
+}
+
+/// 扫描区块范围内的 BEP-20 转账，按 (区块, log_index) 顺序回调；`tokens` 为空表示全部代币
+pub fn scan_token_transfers(
+    db: &BscDatabase,
+    range: Range<u64>,
+    tokens: Vec<Address>,
+    engine: &ParallelScan,
+    checkpointer: Option<&Checkpointer>,
+    resume: bool,
+    mut on_transfer: impl FnMut(TokenTransfer) -> Result<()>,
+) -> Result<()> {
+    let filter = LogFilter { addresses: tokens, topics: [vec![TRANSFER_TOPIC], vec![], vec![], vec![]] };
+    scan_logs(db, range, filter, engine, checkpointer, resume, |log| match TokenTransfer::from_log(&log) {
+        Some(transfer) => on_transfer(transfer),
+        None => Ok(()),
+    })
+}
+
+/// 返回 `range`（左闭右开）内全部 BEP-20 转账
+pub fn token_transfers(db: &BscDatabase, range: Range<u64>, tokens: Vec<Address>) -> Result<Vec<TokenTransfer>> {
+    ParallelScan::default()
+        .collect_with(|engine, push| scan_token_transfers(db, range, tokens, engine, None, false, push))
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::keccak256;
+
+    use super::*;
+    use crate::test_support::{log, words, BUSD, ROUTER, V2_PAIR};
+
+    #[test]
+    fn decodes_bep20_transfer() {
+        assert_eq!(TRANSFER_TOPIC, keccak256("Transfer(address,address,uint256)"));
+
+        // BUSD 从 WBNB/BUSD Pair 转给 Router：1234.5 BUSD
+        let amount = U256::from(1_234_500_000_000_000_000_000u128);
+        let topics = [TRANSFER_TOPIC, V2_PAIR.into_word(), ROUTER.into_word()];
+        let transfer = TokenTransfer::from_log(&log(BUSD, &topics, &words(&[amount]))).expect("bep-20 transfer");
+        assert_eq!((transfer.token, transfer.from, transfer.to), (BUSD, V2_PAIR, ROUTER));
+        assert_eq!(transfer.amount, amount);
+
+        // 铸造：from 为零地址
+        let topics = [TRANSFER_TOPIC, B256::ZERO, ROUTER.into_word()];
+        let mint = TokenTransfer::from_log(&log(BUSD, &topics, &words(&[U256::from(1)]))).expect("mint");
+        assert_eq!(mint.from, Address::ZERO);
+    }
+
+    #[test]
+    fn erc721_transfer_is_not_bep20() {
+        // 同一 topic0，ERC-721 把 tokenId 放在 topic3、data 为空
+        let token_id = B256::with_last_byte(42);
+        let topics = [TRANSFER_TOPIC, V2_PAIR.into_word(), ROUTER.into_word(), token_id];
+        assert!(TokenTransfer::from_log(&log(BUSD, &topics, &[])).is_none());
+        // 非标准实现：4 个 topic 又带 32 字节 data，仍不按 BEP-20 解读
+        assert!(TokenTransfer::from_log(&log(BUSD, &topics, &words(&[U256::from(1)]))).is_none());
+    }
+
+    #[test]
+    fn rejects_malformed_topic_counts_and_data() {
+        let amount = words(&[U256::from(1)]);
+        // from/to 未 indexed（只有 topic0 或少一个地址）
+        assert!(TokenTransfer::from_log(&log(BUSD, &[TRANSFER_TOPIC], &amount)).is_none());
+        assert!(TokenTransfer::from_log(&log(BUSD, &[TRANSFER_TOPIC, V2_PAIR.into_word()], &amount)).is_none());
+        assert!(TokenTransfer::from_log(&log(BUSD, &[], &amount)).is_none());
+
+        let topics = [TRANSFER_TOPIC, V2_PAIR.into_word(), ROUTER.into_word()];
+        assert!(TokenTransfer::from_log(&log(BUSD, &topics, &amount[..31])).is_none());
+        assert!(TokenTransfer::from_log(&log(BUSD, &topics, &words(&[U256::from(1), U256::from(2)]))).is_none());
+
+        let approval = [keccak256("Approval(address,address,uint256)"), V2_PAIR.into_word(), ROUTER.into_word()];
+        assert!(TokenTransfer::from_log(&log(BUSD, &approval, &amount)).is_none());
+    }
+
+    #[test]
+    fn topic_address_ignores_high_bytes() {
+        let mut topic = ROUTER.into_word();
+        topic.0[0] = 0xff;
+        assert_eq!(topic_address(&topic), ROUTER);
+    }
+}
@@ -0,0 +1,180 @@
+use alloy_consensus::transaction::SignerRecoverable; // recover_signer()
+use alloy_evm::Evm;
+use alloy_primitives::{Address, Bytes, B256, U256};
+use reth::revm::DatabaseCommit;
+use reth::rpc::types::BlockHashOrNumber;
+use reth_ethereum::{
+    evm::{
+        primitives::ConfigureEvm,
+        EthEvmConfig,
+        revm::{database::StateProviderDatabase, db::CacheDB},
+    },
+    provider::BlockReader,
+};
+use reth_primitives::{Recovered, TransactionSigned};
+use reth_provider::{AccountReader, TransactionsProvider};
+use revm_inspectors::tracing::{TracingInspector, TracingInspectorConfig};
+use serde::Serialize;
+
+use crate::abi::{AbiRegistry, DecodedCall, DecodedEvent};
+use crate::databases::BscDatabase;
+use crate::events::{log_text, EventRegistry};
+use crate::error::{AppError, Result};
+use crate::output::Record;
+
+/// 调用追踪中的一帧（顶层调用 depth 为 0）
+#[derive(Debug, Clone, Serialize)]
+pub struct CallFrame {
+    pub depth: usize,
+    pub kind: String,
+    pub from: Address,
+    pub to: Address,
+    pub value: U256,
+    pub gas_used: u64,
+    pub success: bool,
+    pub input: Bytes,
+    /// 返回数据（revert 时为 revert 数据）
+    pub output: Bytes,
+    /// 按 ABI 注册表解码的调用与返回值
+    pub decoded: Option<DecodedCall>,
+    /// 该帧直接发出的日志（按发出顺序；帧 revert 时不会进入回执）
+    pub logs: Vec<TraceLog>,
+}
+
+/// 追踪中由某一帧发出的日志
+#[derive(Debug, Clone, Serialize)]
+pub struct TraceLog {
+    pub address: Address,
+    pub topics: Vec<B256>,
+    pub data: Bytes,
+    /// 事件签名（来自合约 ABI 或事件签名库）
+    pub event: Option<String>,
+    /// 按合约 ABI 或事件签名库解码的事件
+    pub decoded: Option<DecodedEvent>,
+}
+
+impl Record for CallFrame {
+    fn text(&self) -> String {
+        let call = match &self.decoded {
+            Some(d) => d.to_string(),
+            None => format!("input={} bytes output={} bytes", self.input.len(), self.output.len()),
+        };
+        let indent = "  ".repeat(self.depth);
+        let mut s = format!(
+            "{indent}{} {:#x} -> {:#x} value={} gas_used={}{} {}",
+            self.kind,
+            self.from,
+            self.to,
+            self.value,
+            self.gas_used,
+            if self.success { "" } else { " REVERTED" },
+            call,
+        );
+        for log in &self.logs {
+            let event = log_text(log.decoded.as_ref(), log.event.as_deref(), &log.topics, &log.data);
+            s += &format!("\n{indent}  log {:#x} {event}", log.address);
+        }
+        s
+    }
+}
+
+fn recover(tx: &TransactionSigned) -> Result<Recovered<TransactionSigned>> {
+    let signer = tx
+        .recover_signer()
+        .map_err(|e| AppError::Corrupt(format!("recover signer of tx {}: {e}", tx.hash())))?;
+    Ok(Recovered::new_unchecked(tx.clone(), signer))
+}
+
+/// 重放交易所在区块直到该交易，返回其完整调用树。
+///
+/// 提供 ABI 注册表时解码每一帧的 calldata 与返回数据；按代码哈希匹配时使用区块执行前的状态，
+/// 因此同一区块内新部署的合约只能按地址匹配。各帧日志先按 ABI、再按事件签名库标注与解码。
+pub fn trace_tx(
+    db: &BscDatabase,
+    hash: B256,
+    abis: Option<&AbiRegistry>,
+    events: &EventRegistry,
+) -> Result<Vec<CallFrame>> {
+    let provider = db.provider()?;
+    let (_, meta) = provider
+        .transaction_by_hash_with_meta(hash)?
+        .ok_or_else(|| AppError::NotFound(format!("transaction {hash:#x}")))?;
+    let block = provider
+        .block(BlockHashOrNumber::Number(meta.block_number))?
+        .ok_or_else(|| AppError::NotFound(format!("block {}", meta.block_number)))?;
+    let state_provider = provider.history_by_block_hash(block.header.parent_hash)?;
+    let mut state = CacheDB::new(StateProviderDatabase::new(state_provider.as_ref()));
+
+    let evm_config = EthEvmConfig::new(db.chain_spec.clone());
+    let mut evm_env = evm_config.evm_env(&block.header);
+    evm_env.cfg_env.disable_block_gas_limit = true;
+
+    // 先执行同块内排在前面的交易，得到目标交易执行前的状态
+    let index = meta.index as usize;
+    for tx in block.body.transactions.iter().take(index) {
+        let tx_env = evm_config.tx_env(&recover(tx)?);
+        let mut evm = evm_config.evm_with_env(&mut state, evm_env.clone());
+        let result = evm
+            .transact(tx_env)
+            .map_err(|e| AppError::Evm(format!("tx {}: {e}", tx.hash())))?;
+        state.commit(result.state);
+    }
+
+    let target = block
+        .body
+        .transactions
+        .get(index)
+        .ok_or_else(|| AppError::Corrupt(format!("block {} has no tx at index {index}", meta.block_number)))?;
+    let mut inspector = TracingInspector::new(TracingInspectorConfig::default().set_record_logs(true));
+    let mut evm = evm_config.evm_with_env_and_inspector(&mut state, evm_env, &mut inspector);
+    evm.transact(evm_config.tx_env(&recover(target)?))
+        .map_err(|e| AppError::Evm(format!("tx {hash:#x}: {e}")))?;
+    drop(evm);
+
+    let code_hash = |address: Address| {
+        state_provider.basic_account(&address).ok().flatten().and_then(|a| a.bytecode_hash)
+    };
+    let frames = inspector
+        .traces()
+        .nodes()
+        .iter()
+        .map(|node| {
+            let t = &node.trace;
+            let abi = abis.and_then(|r| r.find(t.address, || code_hash(t.address)));
+            let decoded = abi
+                .filter(|_| !t.kind.is_any_create())
+                .and_then(|abi| AbiRegistry::decode_call(abi, &t.data, Some(&t.output)));
+            // DELEGATECALL/CALLCODE 帧的日志由存储上下文（如代理合约）发出，事件 ABI 也按其地址查找，
+            // 找不到时退回所执行代码的 ABI
+            let emitter = if t.kind.is_delegate() { t.caller } else { t.address };
+            let event_abi = if emitter == t.address {
+                abi
+            } else {
+                abis.and_then(|r| r.find(emitter, || code_hash(emitter))).or(abi)
+            };
+            let logs = node
+                .logs
+                .iter()
+                .map(|log| {
+                    let (topics, data) = (log.raw_log.topics(), &log.raw_log.data);
+                    let (event, decoded) = events.describe_log(event_abi, topics, data);
+                    TraceLog { address: emitter, topics: topics.to_vec(), data: data.clone(), event, decoded }
+                })
+                .collect();
+            CallFrame {
+                depth: t.depth,
+                kind: format!("{:?}", t.kind).to_uppercase(),
+                from: t.caller,
+                to: t.address,
+                value: t.value,
+                gas_used: t.gas_used,
+                success: t.success,
+                input: t.data.clone(),
+                output: t.output.clone(),
+                decoded,
+                logs,
+            }
+        })
+        .collect();
+    Ok(frames)
+}
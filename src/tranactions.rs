@@ -1,37 +1,44 @@
-use std::path::Path;
-
-use eyre::{bail, Result};
 use reth_db::static_file::TransactionMask;
-use reth_ethereum_primitives::EthPrimitives;
 use reth_primitives::TransactionSigned;
 use alloy_consensus::transaction::Transaction; // for to()/value()/nonce() on TransactionSigned
-use reth_provider::providers::StaticFileProvider;
 use reth_static_file_types::StaticFileSegment;
 use crate::databases::BscDatabase;
+use crate::error::{AppError, Result};
+use crate::output::Record;
 use reth_provider::BlockBodyIndicesProvider;
+use alloy_primitives::{Address, B256};
+use serde::Serialize;
+
+/// TransactionsTest 输出：单笔交易摘要（to 为 None 表示合约创建）
+#[derive(Debug, Clone, Serialize)]
+pub struct TransactionRow {
+    pub block: u64,
+    pub tx_num: u64,
+    pub hash: B256,
+    pub to: Option<Address>,
+}
 
-/// 从 static_files 的 Transactions 段精准读取某区块的所有交易并打印摘要
-/// 注意：传入的是数据目录路径（包含 static_files 子目录）
-pub fn test_transactions(path: impl AsRef<Path>, block_number: u64) -> Result<()> {
-    let p = path.as_ref();
-    // 兼容传入数据目录或 static_files 目录
-    let db_path;
-    let static_dir;
-    let candidate = p.join("static_files");
-    if candidate.is_dir() {
-        db_path = p;
-        static_dir = candidate;
-    } else {
-        db_path = p;
-        static_dir = p.to_path_buf();
+impl Record for TransactionRow {
+    fn text(&self) -> String {
+        format!(
+            "block={} tx_num={} hash={:#x} to={}",
+            self.block,
+            self.tx_num,
+            self.hash,
+            self.to
+                .map(|a| format!("{a:#x}"))
+                .unwrap_or_else(|| "create".to_string())
+        )
     }
+}
 
+/// 从 static_files 的 Transactions 段精准读取某区块的所有交易摘要
+pub fn test_transactions(db: &BscDatabase, block_number: u64) -> Result<Vec<TransactionRow>> {
     // 1) 使用 provider 查询该区块的交易范围（起始 tx 编号与 tx 数量）
-    let db = BscDatabase::new(db_path)?;
-    let provider = db.provider_factory.provider()?;
+    let provider = db.provider()?;
     let indices = match provider.block_body_indices(block_number)? {
         Some(idx) => idx,
-        None => bail!("No BlockBodyIndices found for block {block_number}"),
+        None => return Err(AppError::NotFound(format!("BlockBodyIndices for block {block_number}"))),
     };
 
     let start_tx: u64 = indices.first_tx_num();
@@ -39,7 +46,7 @@ pub fn test_transactions(path: impl AsRef<Path>, block_number: u64) -> Result<()
     let end_tx = start_tx + tx_count;
 
     // 2) 打开包含该区块的 Transactions 段
-    let sf = StaticFileProvider::<EthPrimitives>::read_only(static_dir, true)?;
+    let sf = db.static_file_provider();
     let tx_jar = sf.get_segment_provider_from_block(
         StaticFileSegment::Transactions,
         block_number,
@@ -48,31 +55,22 @@ pub fn test_transactions(path: impl AsRef<Path>, block_number: u64) -> Result<()
     let mut tx_cur = tx_jar.cursor()?;
 
     // 4) 遍历该区块内的所有交易，仅在范围内读取
-    let mut printed = 0u64;
+    let mut rows = Vec::with_capacity(tx_count as usize);
     for tx_num in start_tx..end_tx {
         if let Some(tx) = tx_cur.get_one::<TransactionMask<TransactionSigned>>(tx_num.into())? {
-            println!(
-                "block={} tx_num={} hash={:#x} to={}",
-                block_number,
-                tx_num,
-                tx.hash(),
-                tx.to()
-                    .map(|a| format!("{a:#x}"))
-                    .unwrap_or_else(|| "create".to_string())
-            );
-            printed += 1;
+            rows.push(TransactionRow { block: block_number, tx_num, hash: *tx.hash(), to: tx.to() });
         } else {
-            eprintln!("warn: missing tx at tx_num {} (block {})", tx_num, block_number);
+            tracing::warn!(tx_num, block_number, "Missing tx in static file");
         }
     }
 
-    if printed == 0 {
-        bail!(
-            "No transactions printed for block {block_number} (tx range {}..{})",
+    if rows.is_empty() {
+        return Err(AppError::NotFound(format!(
+            "transactions for block {block_number} (tx range {}..{})",
             start_tx,
             end_tx
-        );
+        )));
     }
 
-    Ok(())
+    Ok(rows)
 }
@@ -0,0 +1,299 @@
+use alloy_consensus::transaction::SignerRecoverable; // recover_signer()
+use alloy_consensus::transaction::Transaction;
+use alloy_primitives::{Address, Bytes, Selector, B256, U256};
+use reth_ethereum_primitives::Receipt;
+use reth_provider::{AccountReader, TransactionsProvider};
+use serde::Serialize;
+
+use crate::abi::{AbiRegistry, DecodedCall, DecodedEvent};
+use crate::al::{tx_type_name, SenderSource};
+use crate::databases::BscDatabase;
+use crate::events::{log_text, EventRegistry};
+use crate::error::{AppError, Result};
+use crate::output::Record;
+use crate::system;
+
+/// 访问列表条目（EIP-2930 起）
+#[derive(Debug, Clone, Serialize)]
+pub struct AccessListEntry {
+    pub address: Address,
+    pub storage_keys: Vec<B256>,
+}
+
+/// EIP-7702 授权条目；authority 为签名恢复出的授权人（签名无效时为 None）
+#[derive(Debug, Clone, Serialize)]
+pub struct AuthorizationEntry {
+    pub chain_id: U256,
+    pub address: Address,
+    pub nonce: u64,
+    pub authority: Option<Address>,
+}
+
+/// 单条日志；log_index 为区块内序号（与 eth_getLogs 一致）
+#[derive(Debug, Clone, Serialize)]
+pub struct LogEntry {
+    pub log_index: u64,
+    pub address: Address,
+    pub topics: Vec<B256>,
+    pub data: Bytes,
+    /// 事件签名（来自合约 ABI 或事件签名库）
+    pub event: Option<String>,
+    /// 按合约 ABI 或事件签名库解码的事件
+    pub decoded: Option<DecodedEvent>,
+}
+
+/// 交易回执部分
+#[derive(Debug, Clone, Serialize)]
+pub struct TxReceiptInfo {
+    pub success: bool,
+    pub cumulative_gas_used: u64,
+    pub gas_used: u64,
+    pub fee: U256,
+    pub logs: Vec<LogEntry>,
+}
+
+/// `tx <hash>` 输出：交易全部字段 + 发送者 + 回执 + 日志 + 创建的合约地址
+#[derive(Debug, Clone, Serialize)]
+pub struct TxDetails {
+    pub hash: B256,
+    pub tx_num: u64,
+    pub block_number: u64,
+    pub block_hash: B256,
+    pub index: u64,
+    pub tx_type: &'static str,
+    pub chain_id: Option<u64>,
+    pub from: Address,
+    pub sender_source: SenderSource,
+    pub to: Option<Address>,
+    pub nonce: u64,
+    pub value: U256,
+    pub gas_limit: u64,
+    pub gas_price: Option<u128>,
+    pub max_fee_per_gas: Option<u128>,
+    pub max_priority_fee_per_gas: Option<u128>,
+    pub max_fee_per_blob_gas: Option<u128>,
+    pub effective_gas_price: u128,
+    pub input: Bytes,
+    /// 按 ABI 注册表解码的调用（合约创建不解码）
+    pub decoded_input: Option<DecodedCall>,
+    pub access_list: Option<Vec<AccessListEntry>>,
+    pub authorization_list: Option<Vec<AuthorizationEntry>>,
+    pub blob_versioned_hashes: Option<Vec<B256>>,
+    pub signature_r: U256,
+    pub signature_s: U256,
+    pub y_parity: bool,
+    pub system_contract: Option<&'static str>,
+    pub system_function: Option<&'static str>,
+    /// receipts 被裁剪时为 None
+    pub receipt: Option<TxReceiptInfo>,
+    /// 合约创建交易（且未失败）生成的合约地址
+    pub contract_address: Option<Address>,
+}
+
+/// 按哈希查找交易并汇总全部信息；提供 ABI 注册表时解码 calldata，日志先按 ABI、再按事件签名库标注与解码。
+/// 哈希不存在（或 TransactionHashNumbers 已被裁剪）返回 NotFound。
+pub fn tx_details(
+    db: &BscDatabase,
+    hash: B256,
+    abis: Option<&AbiRegistry>,
+    events: &EventRegistry,
+) -> Result<TxDetails> {
+    let provider = db.provider()?;
+    let not_found = || AppError::NotFound(format!("transaction {hash:#x}"));
+    let tx_num = provider.transaction_id(hash)?.ok_or_else(not_found)?;
+    let (tx, meta) = provider.transaction_by_hash_with_meta(hash)?.ok_or_else(not_found)?;
+
+    let (from, sender_source) = match provider.transaction_sender(tx_num)? {
+        Some(from) => (from, SenderSource::Stored),
+        None => {
+            let from = tx
+                .recover_signer()
+                .map_err(|e| AppError::Corrupt(format!("recover signer of tx {hash:#x}: {e}")))?;
+            (from, SenderSource::Recovered)
+        }
+    };
+
+    let header = db
+        .query_headers_with_blocknumber(meta.block_number)?
+        .ok_or_else(|| AppError::NotFound(format!("header of block {}", meta.block_number)))?
+        .header;
+    let effective_gas_price = tx.effective_gas_price(header.base_fee_per_gas);
+    let selector = tx.input().get(..4).map(Selector::from_slice);
+    let system = system::classify(header.beneficiary, from, tx.to(), U256::from(effective_gas_price), selector);
+
+    let mut receipt = match db.query_block_receipts(meta.block_number) {
+        Ok(receipts) => Some(receipt_info(&receipts, meta.index as usize, effective_gas_price)?),
+        Err(e) if e.is_pruned() => None,
+        Err(e) => return Err(e),
+    };
+
+    // 按代码哈希匹配 ABI 时读取交易所在区块执行后的状态（历史状态被裁剪时只按地址匹配）
+    let state = std::cell::OnceCell::new();
+    let code_hash = |address: Address| {
+        state
+            .get_or_init(|| db.state_at_block(meta.block_number).ok())
+            .as_ref()
+            .and_then(|s| s.basic_account(&address).ok().flatten())
+            .and_then(|a| a.bytecode_hash)
+    };
+    let decoded_input = abis
+        .zip(tx.to())
+        .and_then(|(abis, to)| abis.find(to, || code_hash(to)))
+        .and_then(|abi| AbiRegistry::decode_call(abi, tx.input(), None));
+    for log in receipt.iter_mut().flat_map(|r| r.logs.iter_mut()) {
+        let abi = abis.and_then(|abis| abis.find(log.address, || code_hash(log.address)));
+        (log.event, log.decoded) = events.describe_log(abi, &log.topics, &log.data);
+    }
+    let contract_address = (tx.to().is_none() && receipt.as_ref().is_none_or(|r| r.success))
+        .then(|| from.create(tx.nonce()));
+
+    let signature = tx.signature();
+    Ok(TxDetails {
+        hash,
+        tx_num,
+        block_number: meta.block_number,
+        block_hash: meta.block_hash,
+        index: meta.index,
+        tx_type: tx_type_name(&tx),
+        chain_id: tx.chain_id(),
+        from,
+        sender_source,
+        to: tx.to(),
+        nonce: tx.nonce(),
+        value: tx.value(),
+        gas_limit: tx.gas_limit(),
+        gas_price: tx.gas_price(),
+        max_fee_per_gas: tx.is_dynamic_fee().then(|| tx.max_fee_per_gas()),
+        max_priority_fee_per_gas: tx.max_priority_fee_per_gas(),
+        max_fee_per_blob_gas: tx.max_fee_per_blob_gas(),
+        effective_gas_price,
+        input: tx.input().clone(),
+        decoded_input,
+        access_list: tx.access_list().map(|list| {
+            list.iter()
+                .map(|item| AccessListEntry { address: item.address, storage_keys: item.storage_keys.clone() })
+                .collect()
+        }),
+        authorization_list: tx.authorization_list().map(|list| {
+            list.iter()
+                .map(|auth| AuthorizationEntry {
+                    chain_id: *auth.chain_id(),
+                    address: *auth.address(),
+                    nonce: auth.nonce(),
+                    authority: auth.recover_authority().ok(),
+                })
+                .collect()
+        }),
+        blob_versioned_hashes: tx.blob_versioned_hashes().map(|h| h.to_vec()),
+        signature_r: signature.r(),
+        signature_s: signature.s(),
+        y_parity: signature.v(),
+        system_contract: system.map(|s| s.contract),
+        system_function: system.and_then(|s| s.function),
+        receipt,
+        contract_address,
+    })
+}
+
+/// 由区块全部回执计算第 `index` 笔的 gas_used 与区块内日志序号
+fn receipt_info(receipts: &[Receipt], index: usize, effective_gas_price: u128) -> Result<TxReceiptInfo> {
+    let receipt = receipts
+        .get(index)
+        .ok_or_else(|| AppError::Corrupt(format!("receipt {index} missing, block has {}", receipts.len())))?;
+    let prev_cumulative = index.checked_sub(1).map(|i| receipts[i].cumulative_gas_used).unwrap_or_default();
+    let gas_used = receipt.cumulative_gas_used.saturating_sub(prev_cumulative);
+    let first_log_index: usize = receipts[..index].iter().map(|r| r.logs.len()).sum();
+    Ok(TxReceiptInfo {
+        success: receipt.success,
+        cumulative_gas_used: receipt.cumulative_gas_used,
+        gas_used,
+        fee: U256::from(gas_used) * U256::from(effective_gas_price),
+        logs: receipt
+            .logs
+            .iter()
+            .enumerate()
+            .map(|(i, log)| LogEntry {
+                log_index: (first_log_index + i) as u64,
+                address: log.address,
+                topics: log.data.topics().to_vec(),
+                data: log.data.data.clone(),
+                event: None,
+                decoded: None,
+            })
+            .collect(),
+    })
+}
+
+impl Record for TxDetails {
+    fn text(&self) -> String {
+        let mut s = format!(
+            "Tx {:#x}\n  block: {} ({:#x}) index={} tx_num={}\n  type: {} chain_id={}\n  from: {:#x} ({:?})\n  to: {}\n  nonce: {} value: {}\n  gas_limit: {} gas_price: {} max_fee: {} max_priority_fee: {} max_blob_fee: {} effective_gas_price: {}\n  input: {} bytes\n",
+            self.hash,
+            self.block_number,
+            self.block_hash,
+            self.index,
+            self.tx_num,
+            self.tx_type,
+            opt(self.chain_id),
+            self.from,
+            self.sender_source,
+            self.to.map(|a| format!("{a:#x}")).unwrap_or_else(|| "(create)".to_string()),
+            self.nonce,
+            self.value,
+            self.gas_limit,
+            opt(self.gas_price),
+            opt(self.max_fee_per_gas),
+            opt(self.max_priority_fee_per_gas),
+            opt(self.max_fee_per_blob_gas),
+            self.effective_gas_price,
+            self.input.len(),
+        );
+        if let Some(call) = &self.decoded_input {
+            s += &format!("  call: {call}\n");
+        }
+        if let (Some(c), f) = (self.system_contract, self.system_function) {
+            s += &format!("  system: {c} {}\n", f.unwrap_or("(unknown function)"));
+        }
+        for item in self.access_list.iter().flatten() {
+            s += &format!("  access: {:#x} keys={}\n", item.address, item.storage_keys.len());
+        }
+        for auth in self.authorization_list.iter().flatten() {
+            s += &format!(
+                "  authorization: chain_id={} address={:#x} nonce={} authority={}\n",
+                auth.chain_id,
+                auth.address,
+                auth.nonce,
+                auth.authority.map(|a| format!("{a:#x}")).unwrap_or_else(|| "invalid".to_string()),
+            );
+        }
+        for h in self.blob_versioned_hashes.iter().flatten() {
+            s += &format!("  blob: {h:#x}\n");
+        }
+        match &self.receipt {
+            Some(r) => {
+                s += &format!(
+                    "  receipt: status={} gas_used={} cumulative_gas_used={} fee={} logs={}\n",
+                    if r.success { "ok" } else { "failed" },
+                    r.gas_used,
+                    r.cumulative_gas_used,
+                    r.fee,
+                    r.logs.len(),
+                );
+                for log in &r.logs {
+                    let event = log_text(log.decoded.as_ref(), log.event.as_deref(), &log.topics, &log.data);
+                    s += &format!("    log #{} {:#x} {event}\n", log.log_index, log.address);
+                }
+            }
+            None => s += "  receipt: pruned\n",
+        }
+        if let Some(addr) = self.contract_address {
+            s += &format!("  contract_address: {addr:#x}\n");
+        }
+        s.pop();
+        s
+    }
+}
+
+fn opt<T: ToString>(v: Option<T>) -> String {
+    v.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string())
+}
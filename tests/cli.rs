@@ -0,0 +1,48 @@
+//! 以子进程运行 bsc_scan，检查输出流与退出码
+
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+fn bsc_scan(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_bsc_scan"))
+        .args(args)
+        .env_remove("RUST_LOG")
+        .output()
+        .expect("run bsc_scan")
+}
+
+/// 不存在的数据目录（按测试名与进程号区分）
+fn missing_datadir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("bsc-scan-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn logs_go_to_stderr_and_stdout_holds_only_records() {
+    let datadir = missing_datadir("no-datadir-logs");
+    let out = bsc_scan(&["--format", "ndjson", "--log", "info", "info", "--db-path", datadir.to_str().unwrap()]);
+
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    for line in stdout.lines() {
+        assert!(serde_json::from_str::<serde_json::Value>(line).is_ok(), "non-record line on stdout: {line}");
+    }
+    assert!(!stdout.contains("Starting bsc_scan"), "{stdout}");
+
+    // 日志仍在 stderr；stderr 不是终端，不带 ANSI 颜色
+    let stderr = String::from_utf8(out.stderr).unwrap();
+    assert!(stderr.contains("Starting bsc_scan"), "{stderr}");
+    assert!(stderr.contains("Opening BSC database"), "{stderr}");
+    assert!(!stderr.contains('\x1b'), "{stderr}");
+}
+
+#[test]
+fn missing_datadir_exits_with_database_code() {
+    let datadir = missing_datadir("no-datadir-exit");
+    let out = bsc_scan(&["info", "--db-path", datadir.to_str().unwrap()]);
+
+    let stderr = String::from_utf8(out.stderr).unwrap();
+    assert_eq!(out.status.code(), Some(7), "{stderr}");
+    assert!(stderr.contains("Database error"), "{stderr}");
+    assert!(stderr.contains(&datadir.join("db").display().to_string()), "{stderr}");
+}